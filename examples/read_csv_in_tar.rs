@@ -1,24 +1,15 @@
-use std::io::Read;
-use std::thread::sleep;
-use std::time::Duration;
-
 const URL: &str = "https://josephine.sobornost.net/rpkidata/2023/08/04/rpki-20230804T000430Z.tgz";
 fn main() {
-    let reader = oneio::get_reader(URL).unwrap();
-    let mut ar = tar::Archive::new(reader);
     println!("processing rpkiviews tar file at {URL}");
     println!("searching for any files in tar that ends with .csv");
-    for entry in ar.entries().unwrap() {
-        let mut entry = entry.unwrap();
-        let path = entry.path().unwrap().to_string_lossy().to_string();
-        if path.ends_with("csv") {
-            println!("found file {}", &path);
-            println!("reading content now... (sleep for 3 seconds)");
-            sleep(Duration::from_secs(3));
-            let mut content: String = String::default();
-            entry.read_to_string(&mut content).unwrap();
-            for line in content.lines() {
-                println!("{}", line);
+    for entry in oneio::list_archive(URL).unwrap() {
+        let entry = entry.unwrap();
+        if entry.path.ends_with("csv") {
+            println!("found file {} ({} bytes)", &entry.path, entry.size);
+            // `archive!/member` streams the archive again up to the member.
+            let member = format!("{URL}!/{}", entry.path);
+            for line in oneio::read_lines_lossy(&member).unwrap() {
+                println!("{}", line.unwrap());
             }
         }
     }
@@ -0,0 +1,74 @@
+//! Archive member paths and entry types shared by the archive formats.
+//!
+//! Paths of the form `archive.tar.gz!/inner/file.csv` address a member inside
+//! an archive. Tar archives (feature `archive`) are streamed, see
+//! `tar_archive`; zip archives (feature `zip`) are read through their central
+//! directory, see `zip_archive`.
+
+/// Separator between an archive path and a member path.
+#[cfg(any(feature = "archive", feature = "zip"))]
+pub(crate) const MEMBER_SEPARATOR: &str = "!/";
+
+/// A member of a tar or zip archive, as returned by
+/// [`OneIo::list_archive`](crate::OneIo::list_archive).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Member path inside the archive, usable after `!/` in a path.
+    pub path: String,
+    /// Size of the member data in bytes (0 for directories and links).
+    pub size: u64,
+    /// Kind of the member.
+    pub kind: ArchiveEntryKind,
+}
+
+/// Kind of an [`ArchiveEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveEntryKind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Directory,
+    /// A symbolic link.
+    Symlink,
+    /// A hard link to an earlier member.
+    HardLink,
+    /// Any other member type (device, FIFO, ...).
+    Other,
+}
+
+/// Container format of an archive path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArchiveFormat {
+    #[cfg(feature = "archive")]
+    Tar,
+    #[cfg(feature = "zip")]
+    Zip,
+}
+
+/// Returns the archive format named by the suffix of `path`, if any.
+#[cfg_attr(
+    not(any(feature = "archive", feature = "zip")),
+    allow(unused_variables)
+)]
+pub(crate) fn archive_format(path: &str) -> Option<ArchiveFormat> {
+    #[cfg(feature = "zip")]
+    if crate::zip_archive::is_zip_path(path) {
+        return Some(ArchiveFormat::Zip);
+    }
+    #[cfg(feature = "archive")]
+    if crate::tar_archive::is_tar_path(path) {
+        return Some(ArchiveFormat::Tar);
+    }
+    None
+}
+
+/// Splits `path` into the archive path, the member path and the archive
+/// format, if it addresses a member of an archive (`data.tar.gz!/inner/file.csv`).
+///
+/// Only splits when the part before `!/` looks like an archive, so other
+/// paths containing `!/` are left alone.
+#[cfg(any(feature = "archive", feature = "zip"))]
+pub(crate) fn split_archive_path(path: &str) -> Option<(&str, &str, ArchiveFormat)> {
+    let (archive, member) = path.split_once(MEMBER_SEPARATOR)?;
+    Some((archive, member, archive_format(archive)?))
+}
@@ -10,11 +10,24 @@ use tokio::io::{AsyncRead, AsyncReadExt};
 ///
 /// This is the async version of `get_reader()`. It supports all the same protocols
 /// and compression formats as the sync version.
+///
+/// Concatenated gzip members, bzip2 streams and zstd frames are all decoded,
+/// matching the default of [`OneIoBuilder::multi_member_decoding`](crate::OneIoBuilder::multi_member_decoding).
 #[cfg(feature = "async")]
 pub async fn get_reader_async(path: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>, OneIoError> {
+    get_decoding_reader_async(path, true).await
+}
+
+/// Gets an async reader for `path`, decoding every gzip member and bzip2
+/// stream only with `multi_member`.
+#[cfg(feature = "async")]
+pub(crate) async fn get_decoding_reader_async(
+    path: &str,
+    multi_member: bool,
+) -> Result<Box<dyn AsyncRead + Send + Unpin>, OneIoError> {
     let raw_reader = get_async_reader_raw(path).await?;
     let file_type = crate::file_extension(path);
-    get_async_compression_reader(raw_reader, file_type)
+    get_async_compression_reader(raw_reader, file_type, multi_member)
 }
 
 /// Reads the entire content of a file asynchronously into a string
@@ -116,6 +129,8 @@ async fn get_async_reader_raw(path: &str) -> Result<Box<dyn AsyncRead + Send + U
 fn get_async_compression_reader(
     reader: Box<dyn AsyncRead + Send + Unpin>,
     file_type: &str,
+    #[cfg_attr(not(any(feature = "any_gz", feature = "bz")), allow(unused_variables))]
+    multi_member: bool,
 ) -> Result<Box<dyn AsyncRead + Send + Unpin>, OneIoError> {
     match file_type {
         #[cfg(all(feature = "async", feature = "any_gz"))]
@@ -123,7 +138,9 @@ fn get_async_compression_reader(
             use async_compression::tokio::bufread::GzipDecoder;
             use tokio::io::BufReader;
             let buf_reader = BufReader::new(reader);
-            let decoder = GzipDecoder::new(buf_reader);
+            let mut decoder = GzipDecoder::new(buf_reader);
+            // Decode every member of concatenated files, like the sync reader.
+            decoder.multiple_members(multi_member);
             Ok(Box::new(decoder))
         }
         #[cfg(all(feature = "async", feature = "bz"))]
@@ -131,7 +148,8 @@ fn get_async_compression_reader(
             use async_compression::tokio::bufread::BzDecoder;
             use tokio::io::BufReader;
             let buf_reader = BufReader::new(reader);
-            let decoder = BzDecoder::new(buf_reader);
+            let mut decoder = BzDecoder::new(buf_reader);
+            decoder.multiple_members(multi_member);
             Ok(Box::new(decoder))
         }
         #[cfg(all(feature = "async", feature = "zstd"))]
@@ -139,7 +157,9 @@ fn get_async_compression_reader(
             use async_compression::tokio::bufread::ZstdDecoder;
             use tokio::io::BufReader;
             let buf_reader = BufReader::new(reader);
-            let decoder = ZstdDecoder::new(buf_reader);
+            let mut decoder = ZstdDecoder::new(buf_reader);
+            // The sync zstd reader also decodes concatenated frames.
+            decoder.multiple_members(true);
             Ok(Box::new(decoder))
         }
         #[cfg(all(feature = "async", feature = "lz"))]
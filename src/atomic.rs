@@ -0,0 +1,207 @@
+//! Atomic local writes via a sibling temp file and rename.
+//!
+//! [`AtomicFile`] writes into a hidden temp file next to the destination and
+//! renames it into place on commit, so readers never observe a partially
+//! written file. Uncommitted temp files are removed on drop.
+
+use crate::writer::FinishWrite;
+use crate::OneIoError;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Distinguishes temp files created by the same process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A buffered file writer that only replaces its destination on commit.
+pub(crate) struct AtomicFile {
+    file: Option<BufWriter<File>>,
+    temp_path: PathBuf,
+    dest: PathBuf,
+    fsync: bool,
+    failed: bool,
+}
+
+impl AtomicFile {
+    /// Creates the temp file for `dest`, creating parent directories as needed.
+    ///
+    /// With `fsync`, the file contents and the parent directory are synced
+    /// before and after the rename, so a committed file survives a crash.
+    pub(crate) fn create(dest: &str, fsync: bool) -> Result<Self, OneIoError> {
+        let dest = PathBuf::from(dest);
+        let dir = match dest.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => {
+                std::fs::create_dir_all(dir)?;
+                dir.to_path_buf()
+            }
+            _ => PathBuf::from("."),
+        };
+        let file_name = dest
+            .file_name()
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("not a file path: {}", dest.display()),
+                )
+            })?
+            .to_string_lossy();
+        let temp_path = dir.join(format!(
+            ".{file_name}.oneio-tmp-{}-{}",
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let file = File::options()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        Ok(Self {
+            file: Some(BufWriter::new(file)),
+            temp_path,
+            dest,
+            fsync,
+            failed: false,
+        })
+    }
+
+    /// Flushes the temp file and renames it over the destination.
+    pub(crate) fn commit(mut self) -> Result<(), OneIoError> {
+        self.commit_inner()
+    }
+
+    fn commit_inner(&mut self) -> Result<(), OneIoError> {
+        let Some(writer) = self.file.take() else {
+            return Ok(());
+        };
+        let result = (|| -> Result<(), OneIoError> {
+            if self.failed {
+                return Err(OneIoError::Io(std::io::Error::other(
+                    "not committing after an earlier write error",
+                )));
+            }
+            let file = writer.into_inner().map_err(|e| e.into_error())?;
+            if self.fsync {
+                file.sync_all()?;
+            }
+            drop(file);
+            std::fs::rename(&self.temp_path, &self.dest)?;
+            if self.fsync {
+                sync_parent_dir(&self.dest)?;
+            }
+            Ok(())
+        })();
+
+        if result.is_err() {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+        result
+    }
+
+    fn writer(&mut self) -> std::io::Result<&mut BufWriter<File>> {
+        self.file
+            .as_mut()
+            .ok_or_else(|| std::io::Error::other("atomic file already committed"))
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let result = self.writer()?.write(buf);
+        self.failed |= result.is_err();
+        result
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let result = self.writer()?.flush();
+        self.failed |= result.is_err();
+        result
+    }
+}
+
+impl FinishWrite for AtomicFile {
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+        self.commit()
+    }
+
+    fn discard(self: Box<Self>) {
+        // Dropping removes the temp file and leaves the destination alone.
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Syncs the directory containing `path` so a rename into it is durable.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), OneIoError> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Directory handles cannot be synced on this platform; the rename is still atomic.
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), OneIoError> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("oneio-atomic-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_commit_replaces_destination() {
+        let dir = temp_dir("commit");
+        let dest = dir.join("out.txt");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&dest, "old").unwrap();
+
+        let mut file = AtomicFile::create(dest.to_str().unwrap(), true).unwrap();
+        file.write_all(b"new content").unwrap();
+        // The destination is untouched until commit.
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "old");
+        file.commit().unwrap();
+
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "new content");
+        assert_eq!(dir_entries(&dir), ["out.txt"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_drop_without_commit_removes_temp_file() {
+        let dir = temp_dir("drop");
+        let dest = dir.join("nested/out.txt");
+
+        let mut file = AtomicFile::create(dest.to_str().unwrap(), false).unwrap();
+        file.write_all(b"partial").unwrap();
+        drop(file);
+
+        assert!(!dest.exists());
+        assert!(dir_entries(&dir.join("nested")).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,453 @@
+//! Pluggable storage backends.
+//!
+//! A [`Backend`] turns a path for one URL scheme into raw (still compressed)
+//! bytes and metadata. [`OneIo`](crate::OneIo) looks up the backend registered
+//! for the scheme of each path (`file` for local paths) and layers
+//! decompression, caching and progress tracking on top, so custom schemes get
+//! those for free. Writes go through [`Backend::writer_raw`] below any
+//! compression.
+//!
+//! Register a custom backend with [`OneIoBuilder::backend`](crate::OneIoBuilder::backend):
+//!
+//! ```rust,no_run
+//! use oneio::{Backend, ObjectStat, OneIo, OneIoError};
+//! use std::io::{Read, Seek, SeekFrom};
+//!
+//! struct MemBackend;
+//!
+//! impl Backend for MemBackend {
+//!     fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+//!         let key = path.trim_start_matches("mem://");
+//!         Ok(Box::new(std::io::Cursor::new(key.as_bytes().to_vec())))
+//!     }
+//!
+//!     fn stat(&self, path: &str) -> Result<ObjectStat, OneIoError> {
+//!         let key = path.trim_start_matches("mem://");
+//!         Ok(ObjectStat {
+//!             content_length: Some(key.len() as u64),
+//!             ..Default::default()
+//!         })
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), OneIoError> {
+//! let oneio = OneIo::builder().backend("mem", MemBackend).build()?;
+//! let content = oneio.read_to_string_lossy("mem://hello")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{OneIoError, OneIoWriter};
+#[cfg(feature = "http")]
+use reqwest::blocking::Client;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Scheme used to look up the backend for paths without a `scheme://` prefix.
+pub(crate) const LOCAL_SCHEME: &str = "file";
+
+/// Metadata about a single object, as returned by [`Backend::stat`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectStat {
+    /// Raw content length in bytes, if known.
+    pub content_length: Option<u64>,
+    /// Last modified timestamp as reported by the backend, if available.
+    pub last_modified: Option<String>,
+    /// Entity tag of the object, if available.
+    pub etag: Option<String>,
+}
+
+impl ObjectStat {
+    /// Whether `self` and `other` describe the same version of an object.
+    ///
+    /// ETags are compared when both sides have one, Last-Modified otherwise;
+    /// without a common validator the versions are assumed to differ.
+    /// Differing known content lengths always mean a different version.
+    pub(crate) fn same_version(&self, other: &ObjectStat) -> bool {
+        if let (Some(a), Some(b)) = (self.content_length, other.content_length) {
+            if a != b {
+                return false;
+            }
+        }
+        match (&self.etag, &other.etag) {
+            (Some(a), Some(b)) => a == b,
+            _ => matches!(
+                (&self.last_modified, &other.last_modified),
+                (Some(a), Some(b)) if a == b
+            ),
+        }
+    }
+}
+
+/// Result of [`Backend::reader_raw_if_changed`] when the object changed:
+/// its current metadata and a reader over its raw bytes.
+pub type ChangedObject = (ObjectStat, Box<dyn Read + Send>);
+
+/// A single entry returned by [`Backend::list`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListEntry {
+    /// Entry name relative to the listed location.
+    pub name: String,
+    /// Full path or URL of the entry, usable with other OneIO calls.
+    pub path: String,
+    /// Size in bytes, if known.
+    pub size: Option<u64>,
+    /// Last modified timestamp, if available, as RFC 3339 UTC with whole
+    /// seconds (`2024-01-31T23:00:00Z`) for every built-in backend.
+    ///
+    /// HTTP index pages show times without a zone; they are taken as UTC,
+    /// which matches nginx but not servers rendering local time (Apache).
+    pub last_modified: Option<String>,
+    /// Whether the entry is a directory (or a common prefix for object stores).
+    pub is_dir: bool,
+}
+
+/// Storage backend for one or more URL schemes.
+///
+/// Only [`reader_raw`](Backend::reader_raw) is required; the remaining
+/// operations return [`OneIoError::NotSupported`] unless overridden.
+pub trait Backend: Send + Sync {
+    /// Opens a reader over the raw bytes at `path`, without decompression.
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError>;
+
+    /// Returns metadata about the object at `path`.
+    fn stat(&self, path: &str) -> Result<ObjectStat, OneIoError> {
+        Err(OneIoError::NotSupported(format!("stat: {path}")))
+    }
+
+    /// Checks whether an object exists at `path`.
+    fn exists(&self, path: &str) -> Result<bool, OneIoError> {
+        Err(OneIoError::NotSupported(format!("exists: {path}")))
+    }
+
+    /// Lists the entries directly under `path`.
+    fn list(&self, path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+        Err(OneIoError::NotSupported(format!("list: {path}")))
+    }
+
+    /// Opens a reader over `path` unless it still matches a cached version.
+    ///
+    /// `cached` holds the metadata recorded when a cached copy was fetched.
+    /// Returns `Ok(None)` when the object is known to be unchanged, otherwise
+    /// its current metadata and a reader over its raw bytes.
+    ///
+    /// The default implementation compares a fresh [`stat`](Backend::stat)
+    /// with `cached` and reads the object unless both carry the same ETag (or
+    /// Last-Modified). Backends without `stat` always read it.
+    fn reader_raw_if_changed(
+        &self,
+        path: &str,
+        cached: &ObjectStat,
+    ) -> Result<Option<ChangedObject>, OneIoError> {
+        let current = match self.stat(path) {
+            Ok(current) if current.same_version(cached) => return Ok(None),
+            Ok(current) => current,
+            Err(OneIoError::NotSupported(_)) => ObjectStat::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Some((current, self.reader_raw(path)?)))
+    }
+
+    /// Opens a reader over the raw bytes of `path` starting at `offset`.
+    ///
+    /// Reads at most `len` bytes, or to the end of the object when `len` is
+    /// `None`. A range starting at or past the end yields an empty reader.
+    /// Backends that cannot serve ranges return
+    /// [`OneIoError::RangeNotSupported`].
+    fn read_range(
+        &self,
+        path: &str,
+        _offset: u64,
+        _len: Option<u64>,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        Err(OneIoError::RangeNotSupported(path.to_string()))
+    }
+
+    /// Opens a writer storing raw bytes at `path`.
+    ///
+    /// Written data should only become visible once the writer is
+    /// [finished](OneIoWriter::finish); see [`OneIoWriter::with_finish`] for
+    /// wrapping a writer with a commit step.
+    fn writer_raw(&self, path: &str) -> Result<OneIoWriter, OneIoError> {
+        Err(OneIoError::NotSupported(format!("write: {path}")))
+    }
+
+    /// Returns the local file backing `path`, if the backend stores objects
+    /// as plain files.
+    ///
+    /// [`OneIo`](crate::OneIo) reads, seeks and writes such files directly,
+    /// honoring [`atomic_writes`](crate::OneIoBuilder::atomic_writes), and
+    /// never calls [`writer_raw`](Backend::writer_raw) for them.
+    fn local_file(&self, _path: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Scheme-to-backend lookup table owned by a [`OneIo`](crate::OneIo) client.
+#[derive(Clone)]
+pub(crate) struct BackendRegistry {
+    backends: HashMap<String, Arc<dyn Backend>>,
+}
+
+impl BackendRegistry {
+    /// Creates a registry with the built-in backends for every enabled feature.
+    pub(crate) fn with_defaults(#[cfg(feature = "http")] http_client: &Client) -> Self {
+        let mut registry = Self {
+            backends: HashMap::new(),
+        };
+        registry.insert(LOCAL_SCHEME, Arc::new(LocalBackend));
+
+        #[cfg(feature = "http")]
+        {
+            let http: Arc<dyn Backend> = Arc::new(HttpBackend {
+                client: http_client.clone(),
+            });
+            registry.insert("http", http.clone());
+            registry.insert("https", http);
+        }
+
+        #[cfg(feature = "ftp")]
+        registry.insert("ftp", Arc::new(FtpBackend));
+
+        #[cfg(feature = "s3")]
+        {
+            let s3: Arc<dyn Backend> = Arc::new(S3Backend);
+            registry.insert("s3", s3.clone());
+            registry.insert("r2", s3);
+        }
+
+        registry
+    }
+
+    /// Registers `backend` for `scheme`, replacing any existing entry.
+    pub(crate) fn insert(&mut self, scheme: &str, backend: Arc<dyn Backend>) {
+        self.backends.insert(scheme.to_ascii_lowercase(), backend);
+    }
+
+    /// Returns the backend responsible for `path`.
+    pub(crate) fn for_path(&self, path: &str) -> Result<&Arc<dyn Backend>, OneIoError> {
+        let scheme = crate::get_protocol(path)
+            .unwrap_or(LOCAL_SCHEME)
+            .to_ascii_lowercase();
+        self.backends
+            .get(&scheme)
+            .ok_or_else(|| OneIoError::NotSupported(path.to_string()))
+    }
+}
+
+/// Local filesystem backend. Accepts plain paths and `file://` URLs.
+struct LocalBackend;
+
+impl LocalBackend {
+    fn local_path(path: &str) -> &str {
+        path.strip_prefix("file://").unwrap_or(path)
+    }
+}
+
+impl Backend for LocalBackend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+        Ok(Box::new(std::fs::File::open(Self::local_path(path))?))
+    }
+
+    fn local_file(&self, path: &str) -> Option<PathBuf> {
+        Some(PathBuf::from(Self::local_path(path)))
+    }
+
+    fn stat(&self, path: &str) -> Result<ObjectStat, OneIoError> {
+        let metadata = std::fs::metadata(Self::local_path(path))?;
+        let modified = metadata.modified().ok();
+        Ok(ObjectStat {
+            content_length: Some(metadata.len()),
+            last_modified: modified.and_then(crate::listing::format_system_time),
+            // Nanosecond mtime and size, so rewrites within a second are seen.
+            etag: modified
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|mtime| format!("\"{:x}-{:x}\"", mtime.as_nanos(), metadata.len())),
+        })
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, OneIoError> {
+        Ok(Path::new(Self::local_path(path)).exists())
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+        let dir = Path::new(Self::local_path(path));
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            entries.push(ListEntry {
+                path: dir.join(&name).to_string_lossy().into_owned(),
+                name,
+                size: metadata.is_file().then_some(metadata.len()),
+                last_modified: metadata
+                    .modified()
+                    .ok()
+                    .and_then(crate::listing::format_system_time),
+                is_dir: metadata.is_dir(),
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    fn read_range(
+        &self,
+        path: &str,
+        offset: u64,
+        len: Option<u64>,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        let mut file = std::fs::File::open(Self::local_path(path))?;
+        file.seek(SeekFrom::Start(offset))?;
+        Ok(match len {
+            Some(len) => Box::new(file.take(len)),
+            None => Box::new(file),
+        })
+    }
+}
+
+/// HTTP(S) backend sharing the client's configured reqwest client.
+#[cfg(feature = "http")]
+struct HttpBackend {
+    client: Client,
+}
+
+#[cfg(feature = "http")]
+impl Backend for HttpBackend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+        Ok(Box::new(crate::remote::get_http_reader_raw(
+            path,
+            &self.client,
+        )?))
+    }
+
+    fn stat(&self, path: &str) -> Result<ObjectStat, OneIoError> {
+        crate::remote::get_http_stat(path, &self.client)
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, OneIoError> {
+        crate::remote::http_file_exists(path, &self.client)
+    }
+
+    fn reader_raw_if_changed(
+        &self,
+        path: &str,
+        cached: &ObjectStat,
+    ) -> Result<Option<ChangedObject>, OneIoError> {
+        crate::remote::get_http_reader_if_changed(path, &self.client, cached)
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+        let mut entries = crate::remote::list_http_dir(path, &self.client)?;
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    fn read_range(
+        &self,
+        path: &str,
+        offset: u64,
+        len: Option<u64>,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        crate::remote::get_http_range_reader(path, &self.client, offset, len)
+    }
+}
+
+/// Anonymous FTP backend.
+#[cfg(feature = "ftp")]
+struct FtpBackend;
+
+#[cfg(feature = "ftp")]
+impl Backend for FtpBackend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+        crate::remote::get_ftp_reader_raw(path)
+    }
+
+    fn read_range(
+        &self,
+        path: &str,
+        offset: u64,
+        len: Option<u64>,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        crate::remote::get_ftp_range_reader(path, offset, len)
+    }
+
+    fn stat(&self, _path: &str) -> Result<ObjectStat, OneIoError> {
+        Err(OneIoError::NotSupported(
+            "FTP size determination not yet implemented".to_string(),
+        ))
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+        let mut entries = crate::remote::list_ftp_dir(path)?;
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+}
+
+/// S3-compatible object storage backend, configured from the environment.
+#[cfg(feature = "s3")]
+struct S3Backend;
+
+#[cfg(feature = "s3")]
+impl Backend for S3Backend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+        let (bucket, key) = crate::s3::s3_url_parse(path)?;
+        crate::s3::s3_reader(&bucket, &key)
+    }
+
+    fn writer_raw(&self, path: &str) -> Result<OneIoWriter, OneIoError> {
+        let (bucket, key) = crate::s3::s3_url_parse(path)?;
+        Ok(OneIoWriter::new(Box::new(crate::s3::S3Writer::new(
+            &bucket, &key,
+        )?)))
+    }
+
+    fn stat(&self, path: &str) -> Result<ObjectStat, OneIoError> {
+        let (bucket, key) = crate::s3::s3_url_parse(path)?;
+        let stats = crate::s3::s3_stats(&bucket, &key)?;
+        Ok(ObjectStat {
+            content_length: Some(stats.content_length),
+            last_modified: stats.last_modified,
+            etag: stats.etag,
+        })
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, OneIoError> {
+        let (bucket, key) = crate::s3::s3_url_parse(path)?;
+        crate::s3::s3_exists(&bucket, &key)
+    }
+
+    fn read_range(
+        &self,
+        path: &str,
+        offset: u64,
+        len: Option<u64>,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        let (bucket, key) = crate::s3::s3_url_parse(path)?;
+        crate::s3::s3_range_reader(&bucket, &key, offset, len)
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+        let (scheme, rest) = path
+            .split_once("://")
+            .ok_or_else(|| OneIoError::NotSupported(format!("Invalid S3 URL: {path}")))?;
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(OneIoError::NotSupported(format!("Invalid S3 URL: {path}")));
+        }
+        // `s3://bucket/dir` lists the contents of `dir/`, like a local directory.
+        let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+            prefix.to_string()
+        } else {
+            format!("{prefix}/")
+        };
+
+        let mut entries = crate::s3::s3_list_entries(scheme, bucket, &prefix)?;
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+}
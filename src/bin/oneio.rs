@@ -1,5 +1,5 @@
 use clap::{Parser, Subcommand};
-use std::io::{IsTerminal, Read, Write};
+use std::io::{IsTerminal, Read};
 use std::path::PathBuf;
 use std::process::exit;
 use std::time::Duration;
@@ -17,6 +17,21 @@ fn parse_header(s: &str) -> Result<(String, String), String> {
     Ok((name, value))
 }
 
+/// Parse a rate in bytes per second, with an optional K, M or G suffix
+/// (powers of 1024, curl-compatible).
+fn parse_rate(s: &str) -> Result<u64, String> {
+    let (digits, multiplier) = match s.chars().last().map(|c| c.to_ascii_lowercase()) {
+        Some('k') => (&s[..s.len() - 1], 1 << 10),
+        Some('m') => (&s[..s.len() - 1], 1 << 20),
+        Some('g') => (&s[..s.len() - 1], 1 << 30),
+        _ => (s, 1),
+    };
+    match digits.trim().parse::<u64>() {
+        Ok(rate) if rate > 0 => Ok(rate.saturating_mul(multiplier)),
+        _ => Err(format!("invalid rate, expected e.g. 500K or 2M: {s}")),
+    }
+}
+
 #[derive(Parser)]
 #[clap(author, version)]
 #[clap(propagate_version = true)]
@@ -63,6 +78,10 @@ struct Cli {
     #[clap(long)]
     strict_utf8: bool,
 
+    /// Limit remote transfer bandwidth in bytes per second (e.g. 500K, 2M)
+    #[clap(long, value_parser = clap::builder::ValueParser::new(parse_rate))]
+    limit_rate: Option<u64>,
+
     #[clap(subcommand)]
     command: Option<Commands>,
 }
@@ -75,11 +94,19 @@ enum Commands {
         s3_command: S3Commands,
     },
 
-    /// Generate SHA256 digest
+    /// Generate file digests (SHA256 by default)
     Digest {
         /// File to open, remote or local
         #[clap(name = "FILE")]
         file: PathBuf,
+
+        /// Digest algorithms, comma-separated (md5, sha1, sha256, sha512, blake3, crc32c)
+        #[clap(long, value_delimiter = ',', default_value = "sha256")]
+        algo: Vec<oneio::Algorithm>,
+
+        /// Hash the decompressed content instead of the raw bytes
+        #[clap(long)]
+        decompressed: bool,
     },
 }
 
@@ -130,61 +157,71 @@ enum S3Commands {
     },
 }
 
-/// Downloads `path` to `out_path` with a progress bar on stderr.
+/// Creates a byte progress bar on stderr.
 ///
 /// `indicatif::ProgressBar` is `Clone + Send + Sync`, so no Arc needed.
 /// The bar is shown immediately; if the total size is unknown it shows a spinner.
-fn download_with_progress(
-    oneio: &oneio::OneIo,
-    path: &str,
-    out_path: &str,
-    message: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+fn byte_progress_bar(message: &str) -> Result<indicatif::ProgressBar, Box<dyn std::error::Error>> {
     let pb = indicatif::ProgressBar::new(0);
     pb.set_draw_target(indicatif::ProgressDrawTarget::stderr());
     pb.set_style(
         indicatif::ProgressStyle::default_bar()
             .template(
                 "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] \
-                 {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+                 {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}",
             )?
             .progress_chars("#>-"),
     );
     pb.set_message(message.to_string());
     pb.enable_steady_tick(Duration::from_millis(100));
+    Ok(pb)
+}
 
+/// Downloads `path` to `out_path` with a progress bar on stderr.
+fn download_with_progress(
+    oneio: &oneio::OneIo,
+    path: &str,
+    out_path: &str,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pb = byte_progress_bar(message)?;
     let pb_cb = pb.clone();
-    let (mut reader, total_size) =
-        oneio.get_reader_with_progress(path, move |bytes_read, total_bytes| {
-            if total_bytes > 0 {
-                pb_cb.set_length(total_bytes);
-            }
-            pb_cb.set_position(bytes_read);
-        })?;
-
-    // Set length upfront if we got it from the content-length probe.
-    if let Some(size) = total_size {
-        pb.set_length(size);
-    }
-
-    let mut writer = std::fs::File::create(out_path)?;
-    let mut buffer = [0u8; 8192];
-    loop {
-        match reader.read(&mut buffer) {
-            Ok(0) => break,
-            Ok(n) => writer.write_all(&buffer[..n])?,
-            Err(e) => return Err(Box::new(e)),
+    oneio.download_with_progress(path, out_path, move |bytes_read, total_bytes| {
+        if total_bytes > 0 {
+            pb_cb.set_length(total_bytes);
         }
-    }
+        pb_cb.set_position(bytes_read);
+    })?;
     pb.finish_with_message(format!("Downloaded to {out_path}"));
     Ok(())
 }
 
-fn build_oneio(headers: &[(String, String)]) -> oneio::OneIo {
+/// Uploads `local` to `s3://bucket/key` with a progress bar on stderr.
+fn upload_with_progress(
+    oneio: &oneio::OneIo,
+    local: &str,
+    bucket: &str,
+    key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pb = byte_progress_bar(local)?;
+    let pb_cb = pb.clone();
+    oneio.s3_upload_with_progress(bucket, key, local, move |bytes, total_bytes, parts| {
+        pb_cb.set_length(total_bytes);
+        pb_cb.set_position(bytes);
+        pb_cb.set_message(format!("{parts} parts"));
+    })?;
+    pb.finish_with_message(format!("Uploaded to s3://{bucket}/{key}"));
+    Ok(())
+}
+
+fn build_oneio(headers: &[(String, String)], limit_rate: Option<u64>) -> oneio::OneIo {
     let mut builder = oneio::OneIo::builder();
     for (name, value) in headers {
         builder = builder.header_str(name, value);
     }
+    if let Some(rate) = limit_rate {
+        builder = builder.rate_limit(rate);
+    }
     builder.build().unwrap_or_else(|e| {
         eprintln!("error: failed to create OneIo client: {e}");
         exit(1);
@@ -351,7 +388,7 @@ fn main() {
     let outfile = cli.outfile;
     let use_progress = std::io::stderr().is_terminal();
 
-    let oneio = build_oneio(&cli.headers);
+    let oneio = build_oneio(&cli.headers, cli.limit_rate);
 
     if let Some(command) = cli.command {
         match command {
@@ -363,7 +400,14 @@ fn main() {
                 } => {
                     s3_credentials_or_exit();
                     let local = local_file.to_string_lossy();
-                    match oneio::s3_upload(&bucket, &path, &local) {
+                    let result = if use_progress {
+                        upload_with_progress(&oneio, &local, &bucket, &path)
+                    } else {
+                        oneio
+                            .s3_upload(&bucket, &path, &local)
+                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+                    };
+                    match result {
                         Ok(_) => println!("uploaded to s3://{bucket}/{path}"),
                         Err(e) => {
                             eprintln!("upload error: {e}");
@@ -424,10 +468,24 @@ fn main() {
                 }
             },
 
-            Commands::Digest { file } => {
+            Commands::Digest {
+                file,
+                algo,
+                decompressed,
+            } => {
                 let path = file.to_string_lossy();
-                match oneio::get_sha256_digest(&path) {
-                    Ok(digest) => println!("{digest}"),
+                let mode = if decompressed {
+                    oneio::DigestMode::Decompressed
+                } else {
+                    oneio::DigestMode::Raw
+                };
+                match oneio.get_digests(&path, &algo, mode) {
+                    Ok(digests) if digests.len() == 1 => println!("{}", digests[0]),
+                    Ok(digests) => {
+                        for (algorithm, digest) in algo.iter().zip(digests) {
+                            println!("{algorithm}  {digest}");
+                        }
+                    }
                     Err(e) => {
                         eprintln!("digest error: {e}");
                         exit(1);
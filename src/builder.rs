@@ -1,3 +1,7 @@
+use crate::backend::{Backend, BackendRegistry};
+use crate::cache::OneIoCache;
+use crate::compression::CompressionDetection;
+use crate::rate_limit::RateLimiter;
 use crate::OneIoError;
 #[cfg(feature = "http")]
 use reqwest::blocking::Client;
@@ -5,6 +9,8 @@ use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
 #[cfg(all(feature = "http", any(feature = "rustls", feature = "native-tls")))]
 use reqwest::Certificate;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Builder for [`OneIo`], modeled after reqwest's client builder API.
 pub struct OneIoBuilder {
@@ -12,6 +18,18 @@ pub struct OneIoBuilder {
     http_client_builder: reqwest::blocking::ClientBuilder,
     #[cfg(feature = "http")]
     default_headers: HeaderMap,
+    backends: Vec<(String, Arc<dyn Backend>)>,
+    compression_detection: CompressionDetection,
+    atomic_writes: bool,
+    fsync_writes: bool,
+    compression_threads: usize,
+    multi_member_decoding: bool,
+    zstd_window_log_max: Option<u32>,
+    cache_max_age: Option<Duration>,
+    cache_stream_through: bool,
+    cache: Option<OneIoCache>,
+    rate_limit: Option<u64>,
+    rate_limit_per_host: Option<u64>,
 }
 
 impl Default for OneIoBuilder {
@@ -46,6 +64,18 @@ impl OneIoBuilder {
             http_client_builder,
             #[cfg(feature = "http")]
             default_headers: default_http_headers(),
+            backends: Vec::new(),
+            compression_detection: CompressionDetection::default(),
+            atomic_writes: false,
+            fsync_writes: false,
+            compression_threads: 1,
+            multi_member_decoding: true,
+            zstd_window_log_max: None,
+            cache_max_age: None,
+            cache_stream_through: false,
+            cache: None,
+            rate_limit: None,
+            rate_limit_per_host: None,
         }
     }
 
@@ -159,19 +189,204 @@ impl OneIoBuilder {
         self
     }
 
+    /// Registers a storage backend for a URL scheme (e.g. `"mem"` for `mem://...`).
+    ///
+    /// Custom backends take precedence over built-in ones, so registering
+    /// `"https"` or `"file"` replaces the default handler for that scheme.
+    /// Schemes are matched case-insensitively.
+    pub fn backend<B: Backend + 'static>(mut self, scheme: &str, backend: B) -> Self {
+        self.backends.push((scheme.to_string(), Arc::new(backend)));
+        self
+    }
+
+    /// Sets how readers choose a decompression codec.
+    ///
+    /// Defaults to [`CompressionDetection::ExtensionOnly`]. Use a sniffing mode
+    /// for paths whose extension is missing or misleading, such as
+    /// `download?id=123` or `rib.20240101`. Explicit overrides via
+    /// [`OneIo::get_reader_with_type`](crate::OneIo::get_reader_with_type) are
+    /// not affected.
+    pub fn compression_detection(mut self, detection: CompressionDetection) -> Self {
+        self.compression_detection = detection;
+        self
+    }
+
+    /// Makes local writes atomic.
+    ///
+    /// [`OneIo::get_writer`](crate::OneIo::get_writer) and
+    /// [`OneIo::download`](crate::OneIo::download) write to a temp file next
+    /// to the destination and rename it into place only once writing has
+    /// succeeded, so a crash or panic never leaves a half-written file behind.
+    /// Disabled by default.
+    pub fn atomic_writes(mut self, enabled: bool) -> Self {
+        self.atomic_writes = enabled;
+        self
+    }
+
+    /// Fsyncs atomic writes before and after the rename.
+    ///
+    /// Makes committed files durable across power loss at the cost of
+    /// slower writes. Only applies when [`atomic_writes`](Self::atomic_writes)
+    /// is enabled. Disabled by default.
+    pub fn fsync_writes(mut self, enabled: bool) -> Self {
+        self.fsync_writes = enabled;
+        self
+    }
+
+    /// Compresses writer output on `threads` worker threads.
+    ///
+    /// Applies to gzip, zstd and xz output from
+    /// [`OneIo::get_writer`](crate::OneIo::get_writer) and its variants.
+    /// Gzip input is split into 1 MiB blocks compressed independently and
+    /// written as consecutive gzip members, like `pigz --independent`; zstd
+    /// and xz use their native multithreaded encoders. The output stays
+    /// readable by standard single-threaded decoders (`gunzip`, `zstd -d`,
+    /// `xz -d`). bzip2 and lz4 are always single-threaded. Defaults to 1;
+    /// values of 0 and 1 disable parallel compression.
+    pub fn compression_threads(mut self, threads: usize) -> Self {
+        self.compression_threads = threads;
+        self
+    }
+
+    /// Decodes every member of concatenated gzip and bzip2 files.
+    ///
+    /// Enabled by default, so output of `cat a.gz b.gz`, bgzip, pigz and
+    /// pbzip2 is read in full. Disable it to stop after the first gzip member
+    /// or bzip2 stream, as earlier oneio releases did; any trailing data is
+    /// then ignored. Applies to [`OneIo::get_reader_async`](crate::OneIo::get_reader_async)
+    /// as well as the sync readers.
+    pub fn multi_member_decoding(mut self, enabled: bool) -> Self {
+        self.multi_member_decoding = enabled;
+        self
+    }
+
+    /// Lets zstd readers decode windows up to 2^`window_log`.
+    ///
+    /// Frames written with a large window, such as
+    /// [`WriterOptions::zstd_window_log`](crate::WriterOptions::zstd_window_log)
+    /// above 27 or `zstd --long`, are rejected by default because libzstd
+    /// caps decoder memory at 2^27 (128 MiB). Raising the limit lets every
+    /// zstd input this client reads allocate a window that large. Values
+    /// outside libzstd's range fail when a zstd reader is opened. Applies to
+    /// the sync readers; unset by default.
+    pub fn zstd_window_log_max(mut self, window_log: u32) -> Self {
+        self.zstd_window_log_max = Some(window_log);
+        self
+    }
+
+    /// Skips revalidation of cache entries validated less than `max_age` ago.
+    ///
+    /// [`OneIo::get_cache_reader`](crate::OneIo::get_cache_reader) normally
+    /// checks with the source on every call whether the cached copy is still
+    /// current. Within `max_age` of the last fetch or successful
+    /// revalidation, the cached copy is used without any request. Entries
+    /// that cannot be revalidated are downloaded again once `max_age` has
+    /// passed. Unset by default.
+    pub fn cache_max_age(mut self, max_age: Duration) -> Self {
+        self.cache_max_age = Some(max_age);
+        self
+    }
+
+    /// Streams downloads to cache readers while they are being cached.
+    ///
+    /// By default, [`OneIo::get_cache_reader`](crate::OneIo::get_cache_reader)
+    /// and [`OneIo::get_cached_reader`](crate::OneIo::get_cached_reader)
+    /// download the whole object before returning a reader. When enabled,
+    /// the returned reader yields decompressed data as it arrives while the
+    /// raw bytes are written to the cache. The entry is committed only once
+    /// the download reaches EOF, and discarded if the reader fails or is
+    /// dropped early. Zip archives are always downloaded first.
+    ///
+    /// Until it is committed or dropped, the reader holds the entry's lock
+    /// against other processes. Other readers of the same entry in this
+    /// process, including ones opened on the same thread, do not wait for
+    /// it: they read the source directly, without caching it.
+    pub fn cache_stream_through(mut self, enabled: bool) -> Self {
+        self.cache_stream_through = enabled;
+        self
+    }
+
+    /// Sets the managed cache used by
+    /// [`OneIo::get_cached_reader`](crate::OneIo::get_cached_reader).
+    ///
+    /// The cache keeps its directory within its byte budget and TTL; see
+    /// [`OneIoCache`]. Pass a clone to keep reading its
+    /// [`stats`](OneIoCache::stats).
+    pub fn cache(mut self, cache: OneIoCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Limits the combined bandwidth of all remote transfers to
+    /// `bytes_per_second`.
+    ///
+    /// Applies to raw reads (and so to every reader and `download`), ranged
+    /// and seekable reads, cache population, resumable HTTP readers, remote
+    /// writers and `OneIo::s3_upload`. The response of
+    /// [`OneIo::get_http_reader_raw`](crate::OneIo::get_http_reader_raw) is
+    /// not limited. The budget is a token bucket holding one second of
+    /// transfer, shared by all clones of the built [`OneIo`](crate::OneIo)
+    /// and all threads using them. Local paths are not limited. Unset by
+    /// default; [`build`](Self::build) fails with an `InvalidInput` error for
+    /// a limit of 0.
+    pub fn rate_limit(mut self, bytes_per_second: u64) -> Self {
+        self.rate_limit = Some(bytes_per_second);
+        self
+    }
+
+    /// Limits the bandwidth of remote transfers to `bytes_per_second` per
+    /// host, where the host is the URL authority (the bucket for S3 paths).
+    ///
+    /// Can be combined with [`rate_limit`](Self::rate_limit), in which case
+    /// transfers respect both budgets. Unset by default; a limit of 0 is
+    /// rejected by [`build`](Self::build).
+    pub fn rate_limit_per_host(mut self, bytes_per_second: u64) -> Self {
+        self.rate_limit_per_host = Some(bytes_per_second);
+        self
+    }
+
     /// Builds a reusable [`OneIo`] instance.
     pub fn build(self) -> Result<crate::client::OneIo, OneIoError> {
         dotenvy::dotenv().ok();
 
+        if self.rate_limit == Some(0) || self.rate_limit_per_host == Some(0) {
+            return Err(OneIoError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "rate limits must be at least 1 byte per second",
+            )));
+        }
+
         #[cfg(feature = "rustls")]
         crate::crypto::ensure_default_provider()?;
 
+        #[cfg(feature = "http")]
+        let http_client = self
+            .http_client_builder
+            .default_headers(self.default_headers)
+            .build()?;
+
+        let mut backends = BackendRegistry::with_defaults(
+            #[cfg(feature = "http")]
+            &http_client,
+        );
+        for (scheme, backend) in self.backends {
+            backends.insert(&scheme, backend);
+        }
+
         Ok(crate::client::OneIo {
             #[cfg(feature = "http")]
-            http_client: self
-                .http_client_builder
-                .default_headers(self.default_headers)
-                .build()?,
+            http_client,
+            backends,
+            compression_detection: self.compression_detection,
+            atomic_writes: self.atomic_writes,
+            fsync_writes: self.fsync_writes,
+            compression_threads: self.compression_threads,
+            multi_member_decoding: self.multi_member_decoding,
+            zstd_window_log_max: self.zstd_window_log_max,
+            cache_max_age: self.cache_max_age,
+            cache_stream_through: self.cache_stream_through,
+            cache: self.cache,
+            rate_limiter: RateLimiter::new(self.rate_limit, self.rate_limit_per_host),
         })
     }
 }
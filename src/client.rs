@@ -1,9 +1,21 @@
-use crate::compression::{get_compression_reader, get_compression_writer};
-#[cfg(any(feature = "http", feature = "ftp"))]
+#[cfg(any(feature = "archive", feature = "zip"))]
+use crate::archive::ArchiveFormat;
+use crate::archive::{self, ArchiveEntry};
+use crate::atomic::AtomicFile;
+use crate::backend::{Backend, BackendRegistry, ListEntry, ObjectStat};
+use crate::cache::{CacheRead, OneIoCache};
+use crate::compression::{
+    get_compression_reader, get_compression_writer, get_detecting_compression_reader,
+    CompressionDetection, WriterOptions,
+};
+use crate::progress::{ProgressCounters, ProgressReader, RawCounter};
+use crate::rate_limit::RateLimiter;
+#[cfg(feature = "http")]
 use crate::remote;
-#[cfg(feature = "s3")]
-use crate::s3;
+use crate::seekable::SeekableReader;
+use crate::writer::{FinishWrite, OneIoWriter};
 use crate::OneIoError;
+use crate::{ProgressEvent, ProgressOptions};
 #[cfg(feature = "http")]
 use reqwest::blocking::Client;
 #[cfg(feature = "json")]
@@ -11,6 +23,9 @@ use serde::de::DeserializeOwned;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Lines, Read, Write};
 use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Private helper: lossy UTF-8 line iterator over any `BufRead`.
 ///
@@ -38,13 +53,24 @@ fn lossy_lines<B: BufRead>(mut buf: B) -> impl Iterator<Item = std::io::Result<S
 
 /// Reusable OneIO client for applying request configuration across multiple operations.
 ///
-/// Use [`OneIo::builder()`] to customize default headers, TLS certificates, and
-/// other HTTP options once, then reuse the resulting client across reads and
-/// downloads.
+/// Use [`OneIo::builder()`] to customize default headers, TLS certificates,
+/// storage backends, and other options once, then reuse the resulting client
+/// across reads and downloads.
 #[derive(Clone)]
 pub struct OneIo {
     #[cfg(feature = "http")]
     pub(crate) http_client: Client,
+    pub(crate) backends: BackendRegistry,
+    pub(crate) compression_detection: CompressionDetection,
+    pub(crate) atomic_writes: bool,
+    pub(crate) fsync_writes: bool,
+    pub(crate) compression_threads: usize,
+    pub(crate) multi_member_decoding: bool,
+    pub(crate) zstd_window_log_max: Option<u32>,
+    pub(crate) cache_max_age: Option<Duration>,
+    pub(crate) cache_stream_through: bool,
+    pub(crate) cache: Option<OneIoCache>,
+    pub(crate) rate_limiter: RateLimiter,
 }
 
 impl OneIo {
@@ -61,7 +87,21 @@ impl OneIo {
     /// Wraps an already-constructed reqwest blocking client.
     #[cfg(feature = "http")]
     pub fn from_client(http_client: Client) -> Self {
-        Self { http_client }
+        let backends = BackendRegistry::with_defaults(&http_client);
+        Self {
+            http_client,
+            backends,
+            compression_detection: CompressionDetection::default(),
+            atomic_writes: false,
+            fsync_writes: false,
+            compression_threads: 1,
+            multi_member_decoding: true,
+            zstd_window_log_max: None,
+            cache_max_age: None,
+            cache_stream_through: false,
+            cache: None,
+            rate_limiter: RateLimiter::default(),
+        }
     }
 
     /// Returns the underlying reqwest blocking client.
@@ -76,37 +116,326 @@ impl OneIo {
     }
 
     /// Creates a writer with compression inferred from the path extension.
-    pub fn get_writer(&self, path: &str) -> Result<Box<dyn Write>, OneIoError> {
-        let output_file = self.get_writer_raw(path)?;
-        let file_type = crate::file_extension(path);
-        get_compression_writer(output_file, file_type)
+    ///
+    /// With the `s3` feature, `s3://` and `r2://` paths stream to object
+    /// storage: data is uploaded in multipart chunks as it is written and the
+    /// upload is completed when the writer is finished. Local paths are
+    /// written to the filesystem, and other schemes through their
+    /// [`Backend::writer_raw`], including custom backends registered with
+    /// [`OneIoBuilder::backend`](crate::OneIoBuilder::backend).
+    ///
+    /// With [`OneIoBuilder::atomic_writes`](crate::OneIoBuilder::atomic_writes),
+    /// local output goes to a sibling temp file that is renamed over `path`
+    /// when the writer is finished. If a write fails, the thread panics or the
+    /// writer is dropped without being finished, the temp file is removed and
+    /// `path` is left untouched.
+    ///
+    /// With [`OneIoBuilder::compression_threads`](crate::OneIoBuilder::compression_threads),
+    /// gzip, zstd and xz output is compressed in parallel.
+    ///
+    /// Call [`OneIoWriter::finish`] to complete the write and surface errors
+    /// from the final flush. Dropping the writer without it discards atomic
+    /// writes, aborts S3 uploads and finalizes plain local files on a
+    /// best-effort basis.
+    pub fn get_writer(&self, path: &str) -> Result<OneIoWriter, OneIoError> {
+        self.open_writer(
+            path,
+            crate::file_extension(path),
+            &WriterOptions::default(),
+            None,
+        )
+        .map(OneIoWriter::new)
+    }
+
+    /// Creates a writer with compression inferred from the path extension and
+    /// encoder settings from `options`.
+    ///
+    /// Otherwise behaves like [`get_writer`](Self::get_writer). Returns an
+    /// `InvalidInput` IO error if a level or preset is out of range for the
+    /// selected codec.
+    pub fn get_writer_with_options(
+        &self,
+        path: &str,
+        options: &WriterOptions,
+    ) -> Result<OneIoWriter, OneIoError> {
+        self.open_writer(path, crate::file_extension(path), options, None)
+            .map(OneIoWriter::new)
+    }
+
+    /// Creates a writer with explicit compression type override.
+    ///
+    /// The counterpart of [`get_reader_with_type`](Self::get_reader_with_type):
+    /// `compression` takes the same suffixes (`"gz"`, `"zst"`, ...), and an
+    /// empty string writes uncompressed output.
+    pub fn get_writer_with_type(
+        &self,
+        path: &str,
+        compression: &str,
+    ) -> Result<OneIoWriter, OneIoError> {
+        self.open_writer(path, compression, &WriterOptions::default(), None)
+            .map(OneIoWriter::new)
+    }
+
+    /// Creates a writer that reports progress while writing.
+    ///
+    /// Otherwise behaves like [`get_writer`](Self::get_writer). `progress`
+    /// receives the uncompressed bytes written so far and the compressed
+    /// bytes handed to the destination, throttled with the default
+    /// [`ProgressOptions`](crate::ProgressOptions), and once more when the
+    /// writer is finished and the codec trailer has been written.
+    pub fn get_writer_with_progress<F>(
+        &self,
+        path: &str,
+        progress: F,
+    ) -> Result<OneIoWriter, OneIoError>
+    where
+        F: Fn(u64, u64) + Send + 'static,
+    {
+        self.get_writer_with_progress_events(
+            path,
+            crate::file_extension(path),
+            &WriterOptions::default(),
+            ProgressOptions::default(),
+            move |event| progress(event.decompressed_bytes, event.raw_bytes),
+        )
+    }
+
+    /// Creates a writer with the given compression type and encoder settings
+    /// that sends [`ProgressEvent`](crate::ProgressEvent)s while writing,
+    /// throttled according to `progress_options`.
+    ///
+    /// `compression` and `options` work as in
+    /// [`get_writer_with_type`](Self::get_writer_with_type) and
+    /// [`get_writer_with_options`](Self::get_writer_with_options); pass the
+    /// path's own suffix (`"gz"` for `out.json.gz`) to keep its codec.
+    /// `raw_bytes` counts the compressed bytes handed to the destination and
+    /// `decompressed_bytes` the uncompressed bytes written; `total_bytes` is
+    /// `None`. A final event with `done` set is sent by
+    /// [`OneIoWriter::finish`](crate::OneIoWriter::finish), after the codec
+    /// trailer has been written.
+    pub fn get_writer_with_progress_events<F>(
+        &self,
+        path: &str,
+        compression: &str,
+        options: &WriterOptions,
+        progress_options: ProgressOptions,
+        progress: F,
+    ) -> Result<OneIoWriter, OneIoError>
+    where
+        F: Fn(&ProgressEvent) + Send + 'static,
+    {
+        let compressed = Arc::new(AtomicU64::new(0));
+        let writer = self.open_writer(path, compression, options, Some(compressed.clone()))?;
+        Ok(OneIoWriter::new(Box::new(
+            crate::progress::ProgressWriter::new(writer, compressed, progress_options, progress),
+        )))
+    }
+
+    /// Opens the destination of `path` behind the codec for `compression`,
+    /// counting the bytes handed to the destination in `compressed`, if given.
+    fn open_writer(
+        &self,
+        path: &str,
+        compression: &str,
+        options: &WriterOptions,
+        compressed: Option<Arc<AtomicU64>>,
+    ) -> Result<Box<dyn FinishWrite>, OneIoError> {
+        let mut sink = self.open_sink(path)?;
+        if let Some(compressed) = compressed {
+            sink = Box::new(crate::progress::CountingWriter::new(sink, compressed));
+        }
+        get_compression_writer(sink, compression, options, self.compression_threads)
+    }
+
+    /// Opens the destination of a writer, below any compression.
+    fn open_sink(&self, path: &str) -> Result<Box<dyn FinishWrite>, OneIoError> {
+        let backend = self.backend(path)?;
+        if let Some(file) = backend.local_file(path) {
+            let file = file.to_string_lossy();
+            return Ok(if self.atomic_writes {
+                Box::new(AtomicFile::create(&file, self.fsync_writes)?)
+            } else {
+                Box::new(self.get_writer_raw(&file)?)
+            });
+        }
+        let writer = Box::new(backend.writer_raw(path)?);
+        Ok(match self.rate_limiter.for_path(path) {
+            Some(throttle) => Box::new(throttle.writer(writer)),
+            None => writer,
+        })
+    }
+
+    /// Uploads a local file to an S3 bucket, respecting the client's
+    /// [`rate_limit`](crate::OneIoBuilder::rate_limit).
+    ///
+    /// Otherwise behaves like [`s3_upload`](crate::s3_upload).
+    #[cfg(feature = "s3")]
+    pub fn s3_upload(&self, bucket: &str, key: &str, file_path: &str) -> Result<(), OneIoError> {
+        self.s3_upload_with_progress(bucket, key, file_path, |_, _, _| {})
+    }
+
+    /// Uploads a local file to an S3 bucket, reporting progress and
+    /// respecting the client's [`rate_limit`](crate::OneIoBuilder::rate_limit).
+    ///
+    /// Otherwise behaves like [`s3_upload_with_progress`](crate::s3_upload_with_progress).
+    #[cfg(feature = "s3")]
+    pub fn s3_upload_with_progress<F>(
+        &self,
+        bucket: &str,
+        key: &str,
+        file_path: &str,
+        progress: F,
+    ) -> Result<(), OneIoError>
+    where
+        F: Fn(u64, u64, usize) + Send,
+    {
+        let throttle = self.rate_limiter.for_path(&format!("s3://{bucket}/{key}"));
+        crate::s3::upload_file(bucket, key, file_path, &progress, throttle)
+    }
+
+    /// Returns the storage backend registered for the scheme of `path`.
+    ///
+    /// Paths without a `scheme://` prefix resolve to the `file` backend.
+    pub fn backend(&self, path: &str) -> Result<&Arc<dyn Backend>, OneIoError> {
+        self.backends.for_path(path)
     }
 
     /// Creates a raw reader without decompression.
     pub fn get_reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
-        let raw_reader: Box<dyn Read + Send> = match crate::get_protocol(path) {
-            Some(protocol) => match protocol {
-                #[cfg(feature = "http")]
-                "http" | "https" => Box::new(self.get_http_reader_raw(path)?),
-                #[cfg(feature = "ftp")]
-                "ftp" => remote::get_ftp_reader_raw(path)?,
-                #[cfg(feature = "s3")]
-                "s3" | "r2" => {
-                    let (bucket, path) = s3::s3_url_parse(path)?;
-                    s3::s3_reader(bucket.as_str(), path.as_str())?
-                }
-                _ => return Err(OneIoError::NotSupported(path.to_string())),
-            },
-            None => Box::new(File::open(path)?),
-        };
-        Ok(raw_reader)
+        let reader = self.backend(path)?.reader_raw(path)?;
+        Ok(self.rate_limiter.reader(path, reader))
     }
 
     /// Creates a reader with decompression inferred from the path extension.
+    ///
+    /// With [`OneIoBuilder::compression_detection`](crate::OneIoBuilder::compression_detection)
+    /// the codec can instead be detected from the leading magic bytes.
+    ///
+    /// With the `archive` feature, a member of a tar archive can be read with
+    /// `archive!/member` paths, e.g. `https://host/data.tar.gz!/inner/file.csv.gz`. The archive and
+    /// the member are each decompressed according to their own suffix, and
+    /// the archive is streamed only up to the end of the member.
+    ///
+    /// With the `zip` feature, `archive.zip!/member` paths read zip members
+    /// via the central directory, using ranged requests for remote archives.
+    /// A `.zip` path without a member opens the archive's only file; archives
+    /// with several files are returned as raw archive bytes, as without the
+    /// feature.
     pub fn get_reader(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+        #[cfg(any(feature = "archive", feature = "zip"))]
+        if let Some((archive, member, format)) = archive::split_archive_path(path) {
+            let member_reader = match format {
+                #[cfg(feature = "archive")]
+                ArchiveFormat::Tar => {
+                    crate::tar_archive::member_reader(self.get_reader(archive)?, archive, member)?
+                }
+                #[cfg(feature = "zip")]
+                ArchiveFormat::Zip => crate::zip_archive::member_reader(self, archive, member)?,
+            };
+            return self.get_decompressing_reader(member_reader, member);
+        }
+        #[cfg(feature = "zip")]
+        if crate::zip_archive::is_zip_path(path) {
+            let opened = crate::zip_archive::single_file_reader(self, path)?;
+            return self.read_zip_opened(opened, path);
+        }
         let raw_reader = self.get_reader_raw(path)?;
-        let file_type = crate::file_extension(path);
-        get_compression_reader(raw_reader, file_type)
+        self.get_decompressing_reader(raw_reader, path)
+    }
+
+    /// Decodes what the zip archive at `path` opened to without a member.
+    #[cfg(feature = "zip")]
+    fn read_zip_opened(
+        &self,
+        opened: crate::zip_archive::Opened,
+        path: &str,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        match opened {
+            crate::zip_archive::Opened::File(name, member_reader) => {
+                self.get_decompressing_reader(member_reader, &name)
+            }
+            crate::zip_archive::Opened::Archive(raw_reader) => {
+                self.get_decompressing_reader(raw_reader, path)
+            }
+        }
+    }
+
+    /// Lists the members of a tar (feature `archive`) or zip (feature `zip`)
+    /// archive.
+    ///
+    /// Tar archives are decompressed according to their suffix and streamed:
+    /// entries are yielded as their headers are read, and member data is
+    /// skipped without being buffered. Zip archives (feature `zip`) are listed
+    /// from the central directory alone. Member paths can be appended to the
+    /// archive path after `!/` to read them with [`get_reader`](Self::get_reader).
+    pub fn list_archive(
+        &self,
+        path: &str,
+    ) -> Result<impl Iterator<Item = Result<ArchiveEntry, OneIoError>> + Send, OneIoError> {
+        let entries: Box<dyn Iterator<Item = Result<ArchiveEntry, OneIoError>> + Send> =
+            match archive::archive_format(path) {
+                #[cfg(feature = "zip")]
+                Some(ArchiveFormat::Zip) => {
+                    Box::new(crate::zip_archive::list(self, path)?.into_iter().map(Ok))
+                }
+                #[cfg(feature = "archive")]
+                _ => Box::new(crate::tar_archive::ArchiveEntries::new(
+                    self.get_reader(path)?,
+                )),
+                #[cfg(not(feature = "archive"))]
+                _ => Err(OneIoError::NotSupported(format!("list_archive: {path}")))?,
+            };
+        Ok(entries)
+    }
+
+    /// Wraps `raw_reader` in the decoder selected by the configured
+    /// [`CompressionDetection`] mode.
+    fn get_decompressing_reader(
+        &self,
+        raw_reader: Box<dyn Read + Send>,
+        path: &str,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        get_detecting_compression_reader(
+            raw_reader,
+            crate::file_extension(path),
+            self.compression_detection,
+            self.multi_member_decoding,
+            self.zstd_window_log_max,
+        )
+    }
+
+    /// Opens a reader supporting random access via [`Seek`](std::io::Seek).
+    ///
+    /// Local files are read directly. Remote paths are served with ranged
+    /// reads through the path's [`Backend::read_range`] (HTTP `Range`
+    /// requests, S3 ranged `GetObject`) behind a small read-ahead buffer.
+    /// No decompression is applied.
+    ///
+    /// Returns [`OneIoError::RangeNotSupported`] if the backend or server
+    /// does not support byte ranges.
+    pub fn get_seekable_reader(&self, path: &str) -> Result<SeekableReader, OneIoError> {
+        let backend = self.backend(path)?;
+        match backend.local_file(path) {
+            Some(file) => SeekableReader::local(&file),
+            None => SeekableReader::remote(backend.clone(), path, self.rate_limiter.clone()),
+        }
+    }
+
+    /// Reads `len` raw bytes of `path` starting at `offset`.
+    ///
+    /// Issues a real ranged request: HTTP(S) `Range`, S3 ranged `GetObject`
+    /// and FTP `REST`; local files seek and read. No decompression is
+    /// applied. The result is shorter than `len` only if the object ends
+    /// first, and empty if `offset` is at or past the end.
+    ///
+    /// Returns [`OneIoError::RangeNotSupported`] if the backend or server
+    /// ignores the range.
+    pub fn read_range(&self, path: &str, offset: u64, len: u64) -> Result<Vec<u8>, OneIoError> {
+        let reader = self.backend(path)?.read_range(path, offset, Some(len))?;
+        let reader = self.rate_limiter.reader(path, reader);
+        let mut bytes = Vec::with_capacity(len.min(8 * 1024 * 1024) as usize);
+        reader.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
     }
 
     /// Creates a reader with explicit compression type override.
@@ -119,10 +448,44 @@ impl OneIo {
         compression: &str,
     ) -> Result<Box<dyn Read + Send>, OneIoError> {
         let raw_reader = self.get_reader_raw(path)?;
-        get_compression_reader(raw_reader, compression)
+        get_compression_reader(
+            raw_reader,
+            compression,
+            self.multi_member_decoding,
+            self.zstd_window_log_max,
+        )
     }
 
     /// Creates a reader backed by a local cache file.
+    ///
+    /// The raw object is stored as `cache_dir/cache_file_name` together with
+    /// a `.meta` sidecar holding its ETag, Last-Modified and fetch time. The
+    /// default file name is a hash of the normalized full URL, including the
+    /// query, followed by the original file extension (`.gz`, or `.tar.gz`
+    /// for tarballs), so the same file name on different hosts or buckets
+    /// does not collide. `cache_dir/index.tsv` maps the file names back to
+    /// their URLs. Credentials in the URL (`user:password@`) are left out,
+    /// but query strings are written as is, so tokens passed in the query
+    /// (for example presigned URLs) end up on disk. Later calls revalidate the cached copy
+    /// before using it: HTTP(S) sends a conditional GET with
+    /// `If-None-Match`/`If-Modified-Since` and serves the cache on
+    /// `304 Not Modified`, while S3 and local sources compare a fresh
+    /// [`stat`](Self::stat) (an S3 `HEAD`). Changed objects are downloaded
+    /// again.
+    ///
+    /// With [`OneIoBuilder::cache_max_age`](crate::OneIoBuilder::cache_max_age),
+    /// entries validated within the max age are used without contacting the
+    /// source. Sources that report neither an ETag nor a Last-Modified time,
+    /// and cache files without a sidecar, are reused until the max age
+    /// expires, or indefinitely without one. `force_cache` always downloads
+    /// a fresh copy.
+    ///
+    /// Concurrent callers for the same entry, in this process or in others
+    /// sharing `cache_dir`, wait for a single download instead of each
+    /// starting their own. Downloads go to a temp file that is renamed into
+    /// place, so readers never see a partially written cache file. With
+    /// [`OneIoBuilder::cache_stream_through`](crate::OneIoBuilder::cache_stream_through),
+    /// the reader consumes the download while it is being cached.
     pub fn get_cache_reader(
         &self,
         path: &str,
@@ -135,40 +498,105 @@ impl OneIo {
             std::fs::create_dir_all(dir_path)?;
         }
 
-        let cache_file_name = cache_file_name.unwrap_or_else(|| {
-            path.split('/')
-                .next_back()
-                .unwrap_or("cached_file")
-                .to_string()
-        });
+        let cache_file_name = cache_file_name.unwrap_or_else(|| crate::cache::cache_key(path));
 
         let cache_file_path = format!("{cache_dir}/{cache_file_name}");
+        let entry = crate::cache::open(
+            self,
+            path,
+            Path::new(&cache_file_path),
+            force_cache,
+            self.cache_max_age,
+        )?;
+        self.read_cache_entry(&cache_file_path, entry)
+    }
+
+    /// Creates a reader through the managed cache set with
+    /// [`OneIoBuilder::cache`](crate::OneIoBuilder::cache).
+    ///
+    /// Entries are named and revalidated like default
+    /// [`get_cache_reader`](Self::get_cache_reader) entries. Each read
+    /// counts as a hit or a miss, and downloads evict least recently used
+    /// entries beyond the cache's byte budget.
+    pub fn get_cached_reader(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+        let cache = self.cache.as_ref().ok_or_else(|| {
+            OneIoError::NotSupported(
+                "no managed cache configured; set one with OneIoBuilder::cache".to_string(),
+            )
+        })?;
+        let (cache_file, entry) = cache.fetch(self, path, self.cache_stream_through)?;
+        self.read_cache_entry(&cache_file.to_string_lossy(), entry)
+    }
 
-        if !force_cache && Path::new(cache_file_path.as_str()).exists() {
-            return self.get_reader(cache_file_path.as_str());
+    /// Reads a cache entry, streaming its download through the decoder if
+    /// the entry is being replaced.
+    fn read_cache_entry(
+        &self,
+        cache_file: &str,
+        entry: CacheRead,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        let download = match entry {
+            CacheRead::Hit(file) => return self.read_cache_file(file, cache_file),
+            CacheRead::Download(download) => *download,
+        };
+        // Zip members are located through the central directory at the end.
+        #[cfg(feature = "zip")]
+        let streamable = self.cache_stream_through && !crate::zip_archive::is_zip_path(cache_file);
+        #[cfg(not(feature = "zip"))]
+        let streamable = self.cache_stream_through;
+        // An entry busy streaming to another reader is read from the source.
+        if !streamable && download.is_cached() {
+            let file = download.finish()?;
+            return self.read_cache_file(file, cache_file);
         }
+        crate::cache::stream_through(download, |raw| {
+            self.get_decompressing_reader(raw, cache_file)
+        })
+    }
 
-        let mut reader = self.get_reader_raw(path)?;
-        let mut writer = self.get_writer_raw(cache_file_path.as_str())?;
-        std::io::copy(&mut reader, &mut writer)?;
-        writer.flush()?;
+    /// Reads an opened cache entry like [`get_reader`](Self::get_reader)
+    /// reads `cache_file`.
+    fn read_cache_file(
+        &self,
+        file: File,
+        cache_file: &str,
+    ) -> Result<Box<dyn Read + Send>, OneIoError> {
+        #[cfg(feature = "zip")]
+        if crate::zip_archive::is_zip_path(cache_file) {
+            let opened = crate::zip_archive::single_file_from_local(file, cache_file)?;
+            return self.read_zip_opened(opened, cache_file);
+        }
+        self.get_decompressing_reader(Box::new(file), cache_file)
+    }
 
-        self.get_reader(cache_file_path.as_str())
+    /// Returns the managed cache set with
+    /// [`OneIoBuilder::cache`](crate::OneIoBuilder::cache), if any.
+    pub fn cache(&self) -> Option<&OneIoCache> {
+        self.cache.as_ref()
     }
 
     /// Checks whether a local or remote path exists.
     pub fn exists(&self, path: &str) -> Result<bool, OneIoError> {
-        match crate::get_protocol(path) {
-            #[cfg(feature = "http")]
-            Some("http" | "https") => remote::http_file_exists(path, self.http_client()),
-            #[cfg(feature = "s3")]
-            Some("s3" | "r2") => {
-                let (bucket, path) = s3::s3_url_parse(path)?;
-                s3::s3_exists(bucket.as_str(), path.as_str())
-            }
-            Some(_) => Err(OneIoError::NotSupported(path.to_string())),
-            None => Ok(Path::new(path).exists()),
-        }
+        self.backend(path)?.exists(path)
+    }
+
+    /// Lists the entries directly under a local directory or remote prefix.
+    ///
+    /// Supported for local directories, `s3://`/`r2://` prefixes (with or
+    /// without a trailing `/`), FTP directories (`MLSD`, falling back to
+    /// `LIST`) and HTTP(S) directories served as Apache, nginx or lighttpd
+    /// autoindex pages. Entries are sorted by name; sizes are `None` for
+    /// directories. HTTP sizes abbreviated by the server (`1.2M`) are
+    /// approximate.
+    ///
+    /// Custom backends may return [`OneIoError::NotSupported`].
+    pub fn list(&self, path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+        self.backend(path)?.list(path)
+    }
+
+    /// Returns metadata for a local or remote path.
+    pub fn stat(&self, path: &str) -> Result<ObjectStat, OneIoError> {
+        self.backend(path)?.stat(path)
     }
 
     /// Reads the full contents of a file or URL into a string.
@@ -197,6 +625,19 @@ impl OneIo {
         Ok(buf)
     }
 
+    /// Gets an async reader for `path`, honoring
+    /// [`multi_member_decoding`](crate::OneIoBuilder::multi_member_decoding).
+    ///
+    /// Supports the same paths as [`get_reader_async`](crate::get_reader_async);
+    /// custom backends and other client settings do not apply.
+    #[cfg(feature = "async")]
+    pub async fn get_reader_async(
+        &self,
+        path: &str,
+    ) -> Result<Box<dyn tokio::io::AsyncRead + Send + Unpin>, OneIoError> {
+        crate::async_reader::get_decoding_reader_async(path, self.multi_member_decoding).await
+    }
+
     /// Reads and deserializes JSON into the requested type.
     #[cfg(feature = "json")]
     pub fn read_json_struct<T: DeserializeOwned>(&self, path: &str) -> Result<T, OneIoError> {
@@ -240,31 +681,61 @@ impl OneIo {
         Ok(self.to_lines_lossy(reader))
     }
 
+    /// Expands a glob pattern into the sorted list of matching paths.
+    ///
+    /// `*` and `?` match within a single path segment, `**` matches any
+    /// number of segments and `{a,b}` matches either alternative, e.g.
+    /// `s3://bucket/2024/*/updates.*.gz` or `data/**/rib.{0000,1200}.bz2`.
+    /// Local patterns walk only the directories they can reach. Remote
+    /// patterns are walked the same way with [`list`](Self::list) through the
+    /// backend registered for their scheme, starting at the literal prefix
+    /// before the first wildcard segment, so keep wildcards late in the path.
+    ///
+    /// A pattern without matches yields an empty list. Schemes whose backend
+    /// cannot list return [`OneIoError::NotSupported`].
+    pub fn glob(&self, pattern: &str) -> Result<Vec<String>, OneIoError> {
+        crate::glob::expand(self, pattern)
+    }
+
+    /// Streams the lines of every file matching `pattern`, in the order
+    /// returned by [`glob`](Self::glob).
+    ///
+    /// Files are opened one at a time as the previous one is exhausted, and
+    /// each is decompressed according to its own suffix. A file that fails to
+    /// open yields an `Err` item in its place.
+    pub fn read_lines_lossy_glob(
+        &self,
+        pattern: &str,
+    ) -> Result<impl Iterator<Item = std::io::Result<String>> + Send, OneIoError> {
+        let paths = self.glob(pattern)?;
+        let oneio = self.clone();
+        Ok(paths.into_iter().flat_map(
+            move |path| -> Box<dyn Iterator<Item = std::io::Result<String>> + Send> {
+                match oneio.get_reader(&path) {
+                    Ok(reader) => Box::new(lossy_lines(BufReader::new(reader))),
+                    Err(OneIoError::Io(e)) => Box::new(std::iter::once(Err(e))),
+                    Err(e) => Box::new(std::iter::once(Err(std::io::Error::other(e)))),
+                }
+            },
+        ))
+    }
+
     /// Determines the raw content length for a local or remote path.
     pub fn get_content_length(&self, path: &str) -> Result<u64, OneIoError> {
-        match crate::get_protocol(path) {
-            #[cfg(feature = "http")]
-            Some(protocol) if protocol == "http" || protocol == "https" => {
-                remote::get_http_content_length(path, self.http_client())
-            }
-            #[cfg(feature = "ftp")]
-            Some(protocol) if protocol == "ftp" => Err(OneIoError::NotSupported(
-                "FTP size determination not yet implemented".to_string(),
-            )),
-            #[cfg(feature = "s3")]
-            Some(protocol) if protocol == "s3" || protocol == "r2" => {
-                let (bucket, key) = s3::s3_url_parse(path)?;
-                let stats = s3::s3_stats(&bucket, &key)?;
-                Ok(stats.content_length)
-            }
-            Some(_) => Err(OneIoError::NotSupported(format!(
-                "Protocol not supported for progress tracking: {path}"
-            ))),
-            None => Ok(std::fs::metadata(path)?.len()),
-        }
+        self.stat(path)?.content_length.ok_or_else(|| {
+            OneIoError::NotSupported(format!(
+                "Cannot determine file size - backend doesn't provide a content length: {path}"
+            ))
+        })
     }
 
     /// Creates a reader that reports progress while reading raw bytes.
+    ///
+    /// `progress` receives the raw bytes read so far and the total size,
+    /// which is 0 if the backend cannot determine it. Calls are throttled
+    /// with the default [`ProgressOptions`](crate::ProgressOptions); see
+    /// [`get_reader_with_progress_events`](Self::get_reader_with_progress_events)
+    /// for decompressed byte counts, throughput and throttling control.
     pub fn get_reader_with_progress<F>(
         &self,
         path: &str,
@@ -273,21 +744,95 @@ impl OneIo {
     where
         F: Fn(u64, u64) + Send + 'static,
     {
-        let (total_size, size_option) = match self.get_content_length(path) {
-            Ok(size) => (size, Some(size)),
-            Err(_) => (0, None),
-        };
+        let total_size = self.get_content_length(path).ok();
+        let reader = self.progress_reader(
+            self.get_reader_raw(path)?,
+            Some(path),
+            total_size,
+            ProgressOptions::default(),
+            raw_progress(progress),
+        )?;
+        Ok((reader, total_size))
+    }
 
-        let raw_reader = self.get_reader_raw(path)?;
-        let progress_reader =
-            crate::progress::ProgressReader::new(raw_reader, total_size, progress);
-        let file_type = crate::file_extension(path);
-        let final_reader = get_compression_reader(Box::new(progress_reader), file_type)?;
+    /// Creates a reader that sends [`ProgressEvent`](crate::ProgressEvent)s
+    /// while reading, throttled according to `options`.
+    ///
+    /// Events carry both raw and decompressed byte counts. A final event with
+    /// `done` set is sent when the reader reaches EOF.
+    pub fn get_reader_with_progress_events<F>(
+        &self,
+        path: &str,
+        options: ProgressOptions,
+        progress: F,
+    ) -> Result<Box<dyn Read + Send>, OneIoError>
+    where
+        F: Fn(&ProgressEvent) + Send + 'static,
+    {
+        let total_size = self.get_content_length(path).ok();
+        self.progress_reader(
+            self.get_reader_raw(path)?,
+            Some(path),
+            total_size,
+            options,
+            progress,
+        )
+    }
 
-        Ok((final_reader, size_option))
+    /// Stacks progress tracking around `raw_reader`, decompressing according
+    /// to `decompress_path` if one is given.
+    fn progress_reader<F>(
+        &self,
+        raw_reader: Box<dyn Read + Send>,
+        decompress_path: Option<&str>,
+        total_size: Option<u64>,
+        options: ProgressOptions,
+        progress: F,
+    ) -> Result<Box<dyn Read + Send>, OneIoError>
+    where
+        F: Fn(&ProgressEvent) + Send + 'static,
+    {
+        let counters = Arc::new(ProgressCounters::default());
+        let mut reader: Box<dyn Read + Send> =
+            Box::new(RawCounter::new(raw_reader, counters.clone()));
+        if let Some(path) = decompress_path {
+            reader = self.get_decompressing_reader(reader, path)?;
+        }
+        Ok(Box::new(ProgressReader::new(
+            reader, counters, total_size, options, progress,
+        )))
+    }
+
+    /// Creates a reader that computes digests while the data is consumed.
+    ///
+    /// `raw` algorithms hash the stored bytes and `decompressed` algorithms
+    /// hash the bytes returned by the reader; either list may be empty. The
+    /// returned [`ReaderDigests`](crate::ReaderDigests) handles yield the hex
+    /// digests once the reader has been read to EOF. Raw digests are only
+    /// published if the decoder consumed the stored bytes to their end.
+    #[cfg(feature = "digest")]
+    pub fn get_reader_with_digest(
+        &self,
+        path: &str,
+        raw: &[crate::Algorithm],
+        decompressed: &[crate::Algorithm],
+    ) -> Result<(Box<dyn Read + Send>, crate::ReaderDigests), OneIoError> {
+        let raw_reader = crate::HashingReader::new(self.get_reader_raw(path)?, raw);
+        let raw_handle = raw_reader.handle();
+        let reader = self.get_decompressing_reader(Box::new(raw_reader), path)?;
+        let reader = crate::HashingReader::new(reader, decompressed);
+        let digests = crate::ReaderDigests {
+            raw: raw_handle,
+            decompressed: reader.handle(),
+        };
+        Ok((Box::new(reader), digests))
     }
 
     /// Returns the blocking HTTP response for a URL.
+    ///
+    /// The response is returned as is, so reading it is not limited by
+    /// [`rate_limit`](crate::OneIoBuilder::rate_limit); use
+    /// [`get_reader_raw`](Self::get_reader_raw) for a limited reader.
     #[cfg(feature = "http")]
     pub fn get_http_reader_raw(
         &self,
@@ -299,9 +844,10 @@ impl OneIo {
     /// Returns an HTTP reader with decompression inferred from the URL suffix.
     #[cfg(feature = "http")]
     pub fn get_http_reader(&self, path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
-        let raw_reader: Box<dyn Read + Send> = Box::new(self.get_http_reader_raw(path)?);
-        let file_type = crate::file_extension(path);
-        get_compression_reader(raw_reader, file_type)
+        let raw_reader = self
+            .rate_limiter
+            .reader(path, Box::new(self.get_http_reader_raw(path)?));
+        self.get_decompressing_reader(raw_reader, path)
     }
 
     /// Returns a resumable HTTP reader
@@ -334,37 +880,126 @@ impl OneIo {
             path.to_string(),
             raw_reader,
         ));
-        let file_type = crate::file_extension(path);
-        get_compression_reader(resumable_raw_reader, file_type)
+        let raw_reader = self.rate_limiter.reader(path, resumable_raw_reader);
+        self.get_decompressing_reader(raw_reader, path)
+    }
+
+    /// Creates a resumable HTTP(S) reader that sends
+    /// [`ProgressEvent`](crate::ProgressEvent)s, throttled according to
+    /// `options`.
+    ///
+    /// Behaves like [`get_resumable_http_reader`](Self::get_resumable_http_reader);
+    /// each reconnection is counted in [`ProgressEvent::resumes`](crate::ProgressEvent::resumes).
+    #[cfg(feature = "http")]
+    pub fn get_resumable_http_reader_with_progress<F>(
+        &self,
+        path: &str,
+        options: ProgressOptions,
+        progress: F,
+    ) -> Result<Box<dyn Read + Send>, OneIoError>
+    where
+        F: Fn(&ProgressEvent) + Send + 'static,
+    {
+        let raw_reader = crate::remote::get_http_reader_raw_with_accept_encoding(
+            path,
+            self.http_client(),
+            "identity",
+        )?;
+        let total_size = raw_reader.content_length();
+        let counters = Arc::new(ProgressCounters::default());
+        let resumable_raw_reader = crate::resumable_http::ResumableHttpReader::new(
+            self.http_client().clone(),
+            path.to_string(),
+            raw_reader,
+        )
+        .with_progress(counters.clone());
+        let raw_reader = self
+            .rate_limiter
+            .reader(path, Box::new(resumable_raw_reader));
+        let reader = self.get_decompressing_reader(
+            Box::new(RawCounter::new(raw_reader, counters.clone())),
+            path,
+        )?;
+        Ok(Box::new(ProgressReader::new(
+            reader, counters, total_size, options, progress,
+        )))
     }
 
     /// Downloads a remote resource to a local path without decompression.
+    ///
+    /// With [`OneIoBuilder::atomic_writes`](crate::OneIoBuilder::atomic_writes),
+    /// `local_path` is only replaced once the whole resource has been written.
     pub fn download(&self, remote_path: &str, local_path: &str) -> Result<(), OneIoError> {
-        let _ = local_path;
-
-        match crate::get_protocol(remote_path) {
-            #[cfg(feature = "http")]
-            Some("http" | "https") => {
-                let mut writer = self.get_writer_raw(local_path)?;
-                let mut response = self.get_http_reader_raw(remote_path)?;
-                response.copy_to(&mut writer)?;
-                Ok(())
-            }
-            #[cfg(feature = "ftp")]
-            Some("ftp") => {
-                let mut writer = self.get_writer_raw(local_path)?;
-                let mut reader = remote::get_ftp_reader_raw(remote_path)?;
-                std::io::copy(&mut reader, &mut writer)?;
-                Ok(())
-            }
-            #[cfg(feature = "s3")]
-            Some("s3" | "r2") => {
-                let (bucket, path) = s3::s3_url_parse(remote_path)?;
-                s3::s3_download(bucket.as_str(), path.as_str(), local_path)?;
-                Ok(())
-            }
-            Some(_) | None => Err(OneIoError::NotSupported(remote_path.to_string())),
+        if crate::get_protocol(remote_path).is_none() {
+            return Err(OneIoError::NotSupported(remote_path.to_string()));
+        }
+        self.copy_to_local(self.get_reader_raw(remote_path)?, local_path)
+    }
+
+    /// Downloads a remote resource to a local path, reporting progress.
+    ///
+    /// Otherwise behaves like [`download`](Self::download). `progress`
+    /// receives the bytes downloaded so far and the total size, which is 0
+    /// if the backend cannot determine it, throttled with the default
+    /// [`ProgressOptions`](crate::ProgressOptions). The total size is
+    /// returned as well, if known.
+    pub fn download_with_progress<F>(
+        &self,
+        remote_path: &str,
+        local_path: &str,
+        progress: F,
+    ) -> Result<Option<u64>, OneIoError>
+    where
+        F: Fn(u64, u64) + Send + 'static,
+    {
+        self.download_with_progress_events(
+            remote_path,
+            local_path,
+            ProgressOptions::default(),
+            raw_progress(progress),
+        )
+    }
+
+    /// Downloads a remote resource to a local path, sending
+    /// [`ProgressEvent`](crate::ProgressEvent)s throttled according to
+    /// `options`. Returns the total size, if known.
+    pub fn download_with_progress_events<F>(
+        &self,
+        remote_path: &str,
+        local_path: &str,
+        options: ProgressOptions,
+        progress: F,
+    ) -> Result<Option<u64>, OneIoError>
+    where
+        F: Fn(&ProgressEvent) + Send + 'static,
+    {
+        if crate::get_protocol(remote_path).is_none() {
+            return Err(OneIoError::NotSupported(remote_path.to_string()));
+        }
+        let total_size = self.get_content_length(remote_path).ok();
+        let reader = self.progress_reader(
+            self.get_reader_raw(remote_path)?,
+            None,
+            total_size,
+            options,
+            progress,
+        )?;
+        self.copy_to_local(reader, local_path)?;
+        Ok(total_size)
+    }
+
+    fn copy_to_local(&self, mut reader: impl Read, local_path: &str) -> Result<(), OneIoError> {
+        if self.atomic_writes {
+            // Dropped without commit (and so removed) if the copy fails.
+            let mut writer = AtomicFile::create(local_path, self.fsync_writes)?;
+            std::io::copy(&mut reader, &mut writer)?;
+            return writer.commit();
         }
+
+        let mut writer = self.get_writer_raw(local_path)?;
+        std::io::copy(&mut reader, &mut writer)?;
+        writer.flush()?;
+        Ok(())
     }
 
     /// Downloads with retry support and exponential backoff.
@@ -388,4 +1023,87 @@ impl OneIo {
             }
         }
     }
+
+    /// Downloads a remote resource to a local path and verifies its checksum.
+    ///
+    /// The digest is computed while the raw bytes are streamed to disk, so the
+    /// file is not read a second time. [`Checksum::Auto`](crate::Checksum::Auto)
+    /// uses the checksum published next to the object: `<url>.sha256`, then
+    /// `<url>.md5`, then the object's line in `SHA256SUMS` in the same
+    /// directory, and fails with a `NotFound` IO error if none exists.
+    ///
+    /// The file is always written atomically, regardless of
+    /// [`OneIoBuilder::atomic_writes`](crate::OneIoBuilder::atomic_writes):
+    /// `local_path` is only replaced once the download completes and its
+    /// checksum matches. On a mismatch [`OneIoError::ChecksumMismatch`] is
+    /// returned, and on any failure `local_path` is left untouched.
+    #[cfg(feature = "digest")]
+    pub fn download_verified(
+        &self,
+        remote_path: &str,
+        local_path: &str,
+        checksum: crate::Checksum,
+    ) -> Result<(), OneIoError> {
+        if crate::get_protocol(remote_path).is_none() {
+            return Err(OneIoError::NotSupported(remote_path.to_string()));
+        }
+
+        let (algorithm, expected) = crate::digest::expected_digest(self, remote_path, checksum)?;
+        let mut reader = self.get_reader_raw(remote_path)?;
+
+        // Dropped without commit (and so removed) on failure or mismatch.
+        let mut writer = AtomicFile::create(local_path, self.fsync_writes)?;
+        let actual = crate::digest::copy_hashing(&mut reader, &mut writer, algorithm)?;
+        if actual != expected {
+            return Err(OneIoError::ChecksumMismatch {
+                path: remote_path.to_string(),
+                algorithm: algorithm.name(),
+                expected,
+                actual,
+            });
+        }
+        writer.commit()
+    }
+
+    /// Calculates the digest of a local or remote file.
+    ///
+    /// With [`DigestMode::Raw`](crate::DigestMode::Raw) the stored bytes are
+    /// hashed, even for compressed files; with
+    /// [`DigestMode::Decompressed`](crate::DigestMode::Decompressed) the
+    /// content returned by [`get_reader`](Self::get_reader) is hashed.
+    #[cfg(feature = "digest")]
+    pub fn get_digest(
+        &self,
+        path: &str,
+        algorithm: crate::Algorithm,
+        mode: crate::DigestMode,
+    ) -> Result<String, OneIoError> {
+        let mut digests = self.get_digests(path, &[algorithm], mode)?;
+        Ok(digests.remove(0))
+    }
+
+    /// Calculates several digests of a local or remote file in a single pass,
+    /// returned in the order of `algorithms`.
+    #[cfg(feature = "digest")]
+    pub fn get_digests(
+        &self,
+        path: &str,
+        algorithms: &[crate::Algorithm],
+        mode: crate::DigestMode,
+    ) -> Result<Vec<String>, OneIoError> {
+        let mut reader = match mode {
+            crate::DigestMode::Raw => self.get_reader_raw(path)?,
+            crate::DigestMode::Decompressed => self.get_reader(path)?,
+        };
+        crate::digest::digest_reader(&mut reader, algorithms)
+    }
+}
+
+/// Adapts a `(bytes, total)` callback to [`ProgressEvent`]s, reporting raw
+/// bytes and 0 for an unknown total.
+fn raw_progress<F>(progress: F) -> impl Fn(&ProgressEvent) + Send + 'static
+where
+    F: Fn(u64, u64) + Send + 'static,
+{
+    move |event| progress(event.raw_bytes, event.total_bytes.unwrap_or(0))
 }
@@ -4,9 +4,10 @@
 //! formats, including gzip, bzip2, lz4, xz, and zstd. The available algorithms depend on enabled
 //! Cargo features.
 
+use crate::writer::FinishWrite;
 use crate::OneIoError;
-use std::fs::File;
-use std::io::{BufWriter, Read, Write};
+use std::io::Read;
+use std::ops::RangeInclusive;
 
 /// Returns a compression reader for the given file suffix.
 ///
@@ -14,21 +15,29 @@ use std::io::{BufWriter, Read, Write};
 /// `file_suffix` (such as `"gz"`, `"bz2"`, `"lz4"`, `"xz"`, or `"zst"`), and returns a
 /// reader that transparently decompresses data as it is read. If the suffix is not recognized,
 /// the original `raw_reader` is returned unchanged.
+///
+/// With `multi_member`, gzip and bzip2 readers decode every concatenated member or stream
+/// (`cat a.gz b.gz`, bgzip, pigz, pbzip2) instead of stopping after the first one.
+/// `zstd_window_log_max` raises the zstd decoder's window limit above libzstd's
+/// default of 2^27; `None` keeps the default.
 pub(crate) fn get_compression_reader(
     raw_reader: Box<dyn Read + Send>,
     file_suffix: &str,
+    #[cfg_attr(not(any(feature = "any_gz", feature = "bz")), allow(unused_variables))]
+    multi_member: bool,
+    #[cfg_attr(not(feature = "zstd"), allow(unused_variables))] zstd_window_log_max: Option<u32>,
 ) -> Result<Box<dyn Read + Send>, OneIoError> {
     match file_suffix {
         #[cfg(feature = "any_gz")]
-        "gz" | "gzip" | "tgz" => gzip::get_reader(raw_reader),
+        "gz" | "gzip" | "tgz" => gzip::get_reader(raw_reader, multi_member),
         #[cfg(feature = "bz")]
-        "bz2" | "bz" => bzip2::get_reader(raw_reader),
+        "bz2" | "bz" | "tbz" | "tbz2" => bzip2::get_reader(raw_reader, multi_member),
         #[cfg(feature = "lz")]
-        "lz4" | "lz" => lz4::get_reader(raw_reader),
+        "lz4" | "lz" | "tlz4" => lz4::get_reader(raw_reader),
         #[cfg(feature = "xz")]
-        "xz" | "xz2" | "lzma" => xz::get_reader(raw_reader),
+        "xz" | "xz2" | "lzma" | "txz" => xz::get_reader(raw_reader),
         #[cfg(feature = "zstd")]
-        "zst" | "zstd" => zstd::get_reader(raw_reader),
+        "zst" | "zstd" | "tzst" => zstd::get_reader(raw_reader, zstd_window_log_max),
         _ => {
             // unknown file type - return the raw bytes reader as is
             Ok(raw_reader)
@@ -36,76 +45,555 @@ pub(crate) fn get_compression_reader(
     }
 }
 
+/// How [`OneIo`](crate::OneIo) readers choose a decompression codec.
+///
+/// Configured with
+/// [`OneIoBuilder::compression_detection`](crate::OneIoBuilder::compression_detection).
+/// Sniffing peeks at the first bytes of the stream for the gzip, bzip2, xz,
+/// zstd and lz4 frame magics; the peeked bytes are replayed, so the stream
+/// seen by the decoder (or by the caller, if nothing matches) is intact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompressionDetection {
+    /// Pick the codec from the path extension only (the default).
+    #[default]
+    ExtensionOnly,
+    /// Pick the codec from the leading magic bytes only, ignoring the
+    /// extension. Streams without a known magic are returned as is.
+    SniffOnly,
+    /// Pick the codec from the leading magic bytes, falling back to the path
+    /// extension when no known magic is found.
+    SniffWithExtensionFallback,
+}
+
+/// Longest magic number recognized by [`detect_compression`].
+const MAX_MAGIC_LEN: usize = 6;
+
+/// Returns the codec suffix matching the magic bytes at the start of `prefix`.
+pub(crate) fn detect_compression(prefix: &[u8]) -> Option<&'static str> {
+    const MAGICS: [(&[u8], &str); 5] = [
+        (&[0x1f, 0x8b], "gz"),
+        (b"BZh", "bz2"),
+        (&[0xfd, b'7', b'z', b'X', b'Z', 0x00], "xz"),
+        (&[0x28, 0xb5, 0x2f, 0xfd], "zst"),
+        (&[0x04, 0x22, 0x4d, 0x18], "lz4"),
+    ];
+    MAGICS
+        .iter()
+        .find(|(magic, _)| prefix.starts_with(magic))
+        .map(|(_, suffix)| *suffix)
+}
+
+/// Peeks at the first bytes of `raw_reader` and returns the detected codec
+/// suffix together with a reader that replays the peeked bytes.
+pub(crate) fn sniff_compression(
+    mut raw_reader: Box<dyn Read + Send>,
+) -> Result<(Option<&'static str>, Box<dyn Read + Send>), OneIoError> {
+    let mut prefix = [0u8; MAX_MAGIC_LEN];
+    let mut filled = 0;
+    while filled < prefix.len() {
+        match raw_reader.read(&mut prefix[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let prefix = prefix[..filled].to_vec();
+    let detected = detect_compression(&prefix);
+    Ok((
+        detected,
+        Box::new(std::io::Cursor::new(prefix).chain(raw_reader)),
+    ))
+}
+
+/// Returns a decompressing reader, choosing the codec according to `detection`.
+pub(crate) fn get_detecting_compression_reader(
+    raw_reader: Box<dyn Read + Send>,
+    file_suffix: &str,
+    detection: CompressionDetection,
+    multi_member: bool,
+    zstd_window_log_max: Option<u32>,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    if detection == CompressionDetection::ExtensionOnly {
+        return get_compression_reader(raw_reader, file_suffix, multi_member, zstd_window_log_max);
+    }
+
+    let (detected, reader) = sniff_compression(raw_reader)?;
+    match (detected, detection) {
+        (Some(suffix), _) => {
+            get_compression_reader(reader, suffix, multi_member, zstd_window_log_max)
+        }
+        (None, CompressionDetection::SniffWithExtensionFallback) => {
+            get_compression_reader(reader, file_suffix, multi_member, zstd_window_log_max)
+        }
+        (None, _) => Ok(reader),
+    }
+}
+
+/// Encoder settings for [`OneIo::get_writer_with_options`](crate::OneIo::get_writer_with_options).
+///
+/// Unset values keep oneio's defaults: gzip level 6, bzip2 level 6, lz4
+/// level 0 (fast mode) with 64 KiB blocks, xz preset 6 and zstd level 3.
+/// Settings for codecs other than the one selected for a path are ignored.
+///
+/// ```rust
+/// use oneio::{Lz4BlockSize, WriterOptions};
+///
+/// let options = WriterOptions::new()
+///     .level(19)
+///     .zstd_long_distance_matching(true)
+///     .lz4_block_size(Lz4BlockSize::Max4MB);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriterOptions {
+    level: Option<i32>,
+    #[cfg_attr(not(feature = "zstd"), allow(dead_code))]
+    zstd_window_log: Option<u32>,
+    #[cfg_attr(not(feature = "zstd"), allow(dead_code))]
+    zstd_long_distance_matching: bool,
+    #[cfg_attr(not(feature = "xz"), allow(dead_code))]
+    xz_preset: Option<u32>,
+    #[cfg_attr(not(feature = "xz"), allow(dead_code))]
+    xz_extreme: bool,
+    #[cfg_attr(not(feature = "lz"), allow(dead_code))]
+    lz4_block_size: Lz4BlockSize,
+    #[cfg_attr(not(feature = "lz"), allow(dead_code))]
+    lz4_checksum: bool,
+}
+
+/// Maximum block size of lz4 frames written by oneio.
+///
+/// Larger blocks compress slightly better at the cost of more memory on both
+/// ends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lz4BlockSize {
+    /// 64 KiB blocks (the default).
+    #[default]
+    Max64KB,
+    /// 256 KiB blocks.
+    Max256KB,
+    /// 1 MiB blocks.
+    Max1MB,
+    /// 4 MiB blocks.
+    Max4MB,
+}
+
+impl WriterOptions {
+    /// Creates options with every codec at its default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the compression level of whichever codec the path selects.
+    ///
+    /// Valid ranges are 0-9 for gzip and xz, 1-9 for bzip2, 0-12 for lz4
+    /// (levels above 2 use the high-compression mode) and the range reported
+    /// by libzstd for zstd, including negative fast levels. An out-of-range
+    /// level fails the writer with an `InvalidInput` error.
+    pub fn level(mut self, level: i32) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Sets the zstd window size as a power of two (10-31 on 64-bit targets).
+    ///
+    /// Decoders reject windows above 2^27 unless given a matching memory
+    /// limit: `zstd --long=N` on the command line, or
+    /// [`OneIoBuilder::zstd_window_log_max`](crate::OneIoBuilder::zstd_window_log_max)
+    /// for oneio's readers.
+    pub fn zstd_window_log(mut self, window_log: u32) -> Self {
+        self.zstd_window_log = Some(window_log);
+        self
+    }
+
+    /// Enables zstd long-distance matching (`--long`), which finds repeats
+    /// far apart in large inputs. Defaults the window to 2^27 unless
+    /// [`zstd_window_log`](Self::zstd_window_log) is set.
+    pub fn zstd_long_distance_matching(mut self, enabled: bool) -> Self {
+        self.zstd_long_distance_matching = enabled;
+        self
+    }
+
+    /// Sets the xz preset (0-9), taking precedence over
+    /// [`level`](Self::level) for xz output.
+    pub fn xz_preset(mut self, preset: u32) -> Self {
+        self.xz_preset = Some(preset);
+        self
+    }
+
+    /// Enables the xz "extreme" variant of the preset (`xz -e`), trading
+    /// compression time for a slightly smaller output.
+    pub fn xz_extreme(mut self, enabled: bool) -> Self {
+        self.xz_extreme = enabled;
+        self
+    }
+
+    /// Sets the lz4 frame block size.
+    pub fn lz4_block_size(mut self, block_size: Lz4BlockSize) -> Self {
+        self.lz4_block_size = block_size;
+        self
+    }
+
+    /// Appends a content checksum to lz4 frames, verified on decompression.
+    pub fn lz4_checksum(mut self, enabled: bool) -> Self {
+        self.lz4_checksum = enabled;
+        self
+    }
+
+    /// Returns the configured level, checked against `range`, or `default`.
+    #[cfg_attr(
+        not(any(
+            feature = "any_gz",
+            feature = "bz",
+            feature = "lz",
+            feature = "xz",
+            feature = "zstd"
+        )),
+        allow(dead_code)
+    )]
+    fn level_in(
+        &self,
+        codec: &str,
+        range: RangeInclusive<i32>,
+        default: i32,
+    ) -> Result<i32, OneIoError> {
+        match self.level {
+            None => Ok(default),
+            Some(level) if range.contains(&level) => Ok(level),
+            Some(level) => Err(invalid_option(format!(
+                "{codec} level must be in {}..={}, got {level}",
+                range.start(),
+                range.end()
+            ))),
+        }
+    }
+}
+
+#[cfg_attr(
+    not(any(
+        feature = "any_gz",
+        feature = "bz",
+        feature = "lz",
+        feature = "xz",
+        feature = "zstd"
+    )),
+    allow(dead_code)
+)]
+fn invalid_option(message: String) -> OneIoError {
+    OneIoError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        message,
+    ))
+}
+
 /// Returns a compression writer for the given file suffix.
 ///
 /// This function selects the appropriate compression algorithm based on the provided
 /// `file_suffix` (such as `"gz"`, `"bz2"`, `"lz4"`, `"xz"`, or `"zst"`), and returns a
-/// writer that transparently compresses data as it is written. If the suffix is not recognized,
-/// the original `raw_writer` is returned unchanged.
+/// writer that transparently compresses data as it is written, configured by `options`.
+/// With `threads` above 1, gzip, xz and zstd compress on that many worker threads.
+/// If the suffix is not recognized, the original `raw_writer` is returned unchanged.
 pub(crate) fn get_compression_writer(
-    raw_writer: BufWriter<File>,
+    raw_writer: Box<dyn FinishWrite>,
     file_suffix: &str,
-) -> Result<Box<dyn Write>, OneIoError> {
+    #[cfg_attr(
+        not(any(
+            feature = "any_gz",
+            feature = "bz",
+            feature = "lz",
+            feature = "xz",
+            feature = "zstd"
+        )),
+        allow(unused_variables)
+    )]
+    options: &WriterOptions,
+    #[cfg_attr(
+        not(any(feature = "any_gz", feature = "xz", feature = "zstd")),
+        allow(unused_variables)
+    )]
+    threads: usize,
+) -> Result<Box<dyn FinishWrite>, OneIoError> {
     match file_suffix {
         #[cfg(feature = "any_gz")]
-        "gz" | "gzip" | "tgz" => gzip::get_writer(raw_writer),
+        "gz" | "gzip" | "tgz" => gzip::get_writer(raw_writer, options, threads),
         #[cfg(feature = "bz")]
-        "bz2" | "bz" => bzip2::get_writer(raw_writer),
+        "bz2" | "bz" | "tbz" | "tbz2" => bzip2::get_writer(raw_writer, options),
         #[cfg(feature = "lz")]
-        "lz4" | "lz" => lz4::get_writer(raw_writer),
+        "lz4" | "lz" | "tlz4" => lz4::get_writer(raw_writer, options),
         #[cfg(feature = "xz")]
-        "xz" | "xz2" | "lzma" => xz::get_writer(raw_writer),
+        "xz" | "xz2" | "lzma" | "txz" => xz::get_writer(raw_writer, options, threads),
         #[cfg(feature = "zstd")]
-        "zst" | "zstd" => zstd::get_writer(raw_writer),
-        _ => Ok(Box::new(raw_writer)),
+        "zst" | "zstd" | "tzst" => zstd::get_writer(raw_writer, options, threads),
+        _ => Ok(raw_writer),
     }
 }
 
 #[cfg(feature = "any_gz")]
 pub(crate) mod gzip {
+    use super::WriterOptions;
+    use crate::writer::FinishWrite;
     use crate::OneIoError;
-    use flate2::read::GzDecoder;
+    use flate2::read::{GzDecoder, MultiGzDecoder};
     use flate2::write::GzEncoder;
     use flate2::Compression;
-    use std::fs::File;
-    use std::io::{BufWriter, Read, Write};
+    use std::collections::VecDeque;
+    use std::io::{Read, Write};
+    use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+
+    /// Input bytes per gzip member in parallel mode.
+    const BLOCK_SIZE: usize = 1024 * 1024;
 
     pub(crate) fn get_reader(
         raw_reader: Box<dyn Read + Send>,
+        multi_member: bool,
     ) -> Result<Box<dyn Read + Send>, OneIoError> {
-        Ok(Box::new(GzDecoder::new(raw_reader)))
+        if multi_member {
+            Ok(Box::new(MultiGzDecoder::new(raw_reader)))
+        } else {
+            Ok(Box::new(GzDecoder::new(raw_reader)))
+        }
+    }
+
+    pub(crate) fn get_writer(
+        raw_writer: Box<dyn FinishWrite>,
+        options: &WriterOptions,
+        threads: usize,
+    ) -> Result<Box<dyn FinishWrite>, OneIoError> {
+        let level = Compression::new(options.level_in("gzip", 0..=9, 6)? as u32);
+        if threads > 1 {
+            return Ok(Box::new(ParallelGzEncoder::new(
+                raw_writer, level, threads,
+            )?));
+        }
+        Ok(Box::new(GzEncoder::new(raw_writer, level)))
+    }
+
+    impl FinishWrite for GzEncoder<Box<dyn FinishWrite>> {
+        fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+            self.finish()?.finish_write()
+        }
+
+        fn discard(self: Box<Self>) {
+            if let Ok(inner) = self.finish() {
+                inner.discard();
+            }
+        }
+    }
+
+    type Member = std::io::Result<Vec<u8>>;
+    type Job = (Vec<u8>, SyncSender<Member>);
+
+    /// Gzip encoder compressing [`BLOCK_SIZE`] blocks on a worker pool.
+    ///
+    /// Each block becomes an independent gzip member and members are written
+    /// in input order, so the output is a valid multi-member gzip stream (as
+    /// produced by `pigz --independent` or `cat a.gz b.gz`). At most two
+    /// blocks per worker are in flight, bounding memory use.
+    pub(crate) struct ParallelGzEncoder {
+        inner: Option<Box<dyn FinishWrite>>,
+        block: Vec<u8>,
+        jobs: Option<Sender<Job>>,
+        pending: VecDeque<Receiver<Member>>,
+        workers: Vec<JoinHandle<()>>,
+        max_pending: usize,
+        members: u64,
+    }
+
+    impl ParallelGzEncoder {
+        fn new(
+            inner: Box<dyn FinishWrite>,
+            level: Compression,
+            threads: usize,
+        ) -> Result<Self, OneIoError> {
+            let (jobs, queue) = mpsc::channel::<Job>();
+            let queue = Arc::new(Mutex::new(queue));
+            let mut workers = Vec::with_capacity(threads);
+            for _ in 0..threads {
+                let queue = Arc::clone(&queue);
+                let worker = std::thread::Builder::new()
+                    .name("oneio-gzip".to_string())
+                    .spawn(move || loop {
+                        let job = match queue.lock() {
+                            Ok(queue) => queue.recv(),
+                            Err(_) => break,
+                        };
+                        let Ok((block, reply)) = job else { break };
+                        let _ = reply.send(compress_member(&block, level));
+                    })?;
+                workers.push(worker);
+            }
+            Ok(Self {
+                inner: Some(inner),
+                block: Vec::with_capacity(BLOCK_SIZE),
+                jobs: Some(jobs),
+                pending: VecDeque::new(),
+                workers,
+                max_pending: threads * 2,
+                members: 0,
+            })
+        }
+
+        /// Queues the current block for compression, writing out finished
+        /// members once too many are in flight.
+        fn submit_block(&mut self) -> std::io::Result<()> {
+            let block = std::mem::replace(&mut self.block, Vec::with_capacity(BLOCK_SIZE));
+            let (reply, member) = mpsc::sync_channel(1);
+            self.jobs
+                .as_ref()
+                .and_then(|jobs| jobs.send((block, reply)).ok())
+                .ok_or_else(|| std::io::Error::other("gzip worker threads have exited"))?;
+            self.pending.push_back(member);
+            self.members += 1;
+            while self.pending.len() > self.max_pending {
+                self.write_next_member()?;
+            }
+            Ok(())
+        }
+
+        /// Waits for the oldest in-flight member and writes it out.
+        fn write_next_member(&mut self) -> std::io::Result<()> {
+            let Some(member) = self.pending.pop_front() else {
+                return Ok(());
+            };
+            let member = member
+                .recv()
+                .map_err(|_| std::io::Error::other("gzip worker thread panicked"))??;
+            self.inner()?.write_all(&member)
+        }
+
+        fn inner(&mut self) -> std::io::Result<&mut Box<dyn FinishWrite>> {
+            self.inner
+                .as_mut()
+                .ok_or_else(|| std::io::Error::other("gzip encoder already finished"))
+        }
+
+        /// Stops the worker pool, waiting for running jobs to complete.
+        fn shutdown(&mut self) {
+            self.jobs = None;
+            for worker in self.workers.drain(..) {
+                let _ = worker.join();
+            }
+        }
+    }
+
+    fn compress_member(block: &[u8], level: Compression) -> Member {
+        let mut encoder = GzEncoder::new(Vec::with_capacity(block.len() / 2 + 64), level);
+        encoder.write_all(block)?;
+        encoder.finish()
+    }
+
+    impl Write for ParallelGzEncoder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.block.len() >= BLOCK_SIZE {
+                self.submit_block()?;
+            }
+            let n = buf.len().min(BLOCK_SIZE - self.block.len());
+            self.block.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        /// Ends the current member early and writes out everything queued.
+        fn flush(&mut self) -> std::io::Result<()> {
+            if !self.block.is_empty() {
+                self.submit_block()?;
+            }
+            while !self.pending.is_empty() {
+                self.write_next_member()?;
+            }
+            self.inner()?.flush()
+        }
+    }
+
+    impl ParallelGzEncoder {
+        /// Writes out all remaining members and returns the inner writer.
+        fn finish_members(&mut self) -> Result<Option<Box<dyn FinishWrite>>, OneIoError> {
+            // Empty input still produces one (empty) member, like `GzEncoder`.
+            if !self.block.is_empty() || self.members == 0 {
+                self.submit_block()?;
+            }
+            while !self.pending.is_empty() {
+                self.write_next_member()?;
+            }
+            self.shutdown();
+            Ok(self.inner.take())
+        }
     }
 
-    pub(crate) fn get_writer(raw_writer: BufWriter<File>) -> Result<Box<dyn Write>, OneIoError> {
-        Ok(Box::new(GzEncoder::new(raw_writer, Compression::default())))
+    impl FinishWrite for ParallelGzEncoder {
+        fn finish_write(mut self: Box<Self>) -> Result<(), OneIoError> {
+            match self.finish_members()? {
+                Some(inner) => inner.finish_write(),
+                None => Ok(()),
+            }
+        }
+
+        fn discard(mut self: Box<Self>) {
+            if let Ok(Some(inner)) = self.finish_members() {
+                inner.discard();
+            }
+        }
+    }
+
+    impl Drop for ParallelGzEncoder {
+        fn drop(&mut self) {
+            self.shutdown();
+        }
     }
 }
 
 #[cfg(feature = "bz")]
 pub(crate) mod bzip2 {
+    use super::WriterOptions;
+    use crate::writer::FinishWrite;
     use crate::OneIoError;
-    use std::fs::File;
-    use std::io::{BufWriter, Read, Write};
+    use bzip2::write::BzEncoder;
+    use std::io::Read;
 
     pub(crate) fn get_reader(
         raw_reader: Box<dyn Read + Send>,
+        multi_member: bool,
     ) -> Result<Box<dyn Read + Send>, OneIoError> {
-        Ok(Box::new(bzip2::read::BzDecoder::new(raw_reader)))
+        if multi_member {
+            Ok(Box::new(bzip2::read::MultiBzDecoder::new(raw_reader)))
+        } else {
+            Ok(Box::new(bzip2::read::BzDecoder::new(raw_reader)))
+        }
     }
 
-    pub(crate) fn get_writer(raw_writer: BufWriter<File>) -> Result<Box<dyn Write>, OneIoError> {
-        Ok(Box::new(bzip2::write::BzEncoder::new(
+    pub(crate) fn get_writer(
+        raw_writer: Box<dyn FinishWrite>,
+        options: &WriterOptions,
+    ) -> Result<Box<dyn FinishWrite>, OneIoError> {
+        let level = options.level_in("bzip2", 1..=9, 6)?;
+        Ok(Box::new(BzEncoder::new(
             raw_writer,
-            bzip2::Compression::default(),
+            bzip2::Compression::new(level as u32),
         )))
     }
+
+    impl FinishWrite for BzEncoder<Box<dyn FinishWrite>> {
+        fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+            self.finish()?.finish_write()
+        }
+
+        fn discard(self: Box<Self>) {
+            if let Ok(inner) = self.finish() {
+                inner.discard();
+            }
+        }
+    }
 }
 
 #[cfg(feature = "lz")]
 pub(crate) mod lz4 {
+    use super::{Lz4BlockSize, WriterOptions};
+    use crate::writer::FinishWrite;
     use crate::OneIoError;
-    use std::fs::File;
-    use std::io::{BufWriter, Read, Write};
+    use lz4::{BlockSize, ContentChecksum};
+    use std::io::Read;
 
     pub(crate) fn get_reader(
         raw_reader: Box<dyn Read + Send>,
@@ -113,44 +601,54 @@ pub(crate) mod lz4 {
         Ok(Box::new(lz4::Decoder::new(raw_reader)?))
     }
 
-    pub(crate) fn get_writer(raw_writer: BufWriter<File>) -> Result<Box<dyn Write>, OneIoError> {
-        let encoder = lz4::EncoderBuilder::new().build(raw_writer)?;
-        Ok(Box::new(Lz4Writer(Some(encoder))))
+    pub(crate) fn get_writer(
+        raw_writer: Box<dyn FinishWrite>,
+        options: &WriterOptions,
+    ) -> Result<Box<dyn FinishWrite>, OneIoError> {
+        let level = options.level_in("lz4", 0..=12, 0)?;
+        let block_size = match options.lz4_block_size {
+            Lz4BlockSize::Max64KB => BlockSize::Max64KB,
+            Lz4BlockSize::Max256KB => BlockSize::Max256KB,
+            Lz4BlockSize::Max1MB => BlockSize::Max1MB,
+            Lz4BlockSize::Max4MB => BlockSize::Max4MB,
+        };
+        let checksum = if options.lz4_checksum {
+            ContentChecksum::ChecksumEnabled
+        } else {
+            ContentChecksum::NoChecksum
+        };
+        let encoder = lz4::EncoderBuilder::new()
+            .level(level as u32)
+            .block_size(block_size)
+            .checksum(checksum)
+            .build(raw_writer)?;
+        Ok(Box::new(encoder))
     }
 
-    /// Wrapper around `lz4::Encoder` that writes the frame end marker on drop.
-    ///
-    /// `lz4::Encoder` has no `Drop` impl — `finish()` must be called explicitly
-    /// to flush the end-of-stream marker. Without it the compressed stream is
-    /// incomplete and the decoder returns 0 bytes.
-    struct Lz4Writer<W: Write>(Option<lz4::Encoder<W>>);
-
-    impl<W: Write> Write for Lz4Writer<W> {
-        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-            self.0.as_mut().unwrap().write(buf)
+    /// `lz4::Encoder` has no `Drop` impl — `finish()` must be called to write
+    /// the end-of-stream marker, otherwise the decoder returns 0 bytes.
+    /// [`OneIoWriter`](crate::OneIoWriter) calls it from `finish` and on drop.
+    impl FinishWrite for lz4::Encoder<Box<dyn FinishWrite>> {
+        fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+            let (writer, result) = self.finish();
+            result?;
+            writer.finish_write()
         }
-        fn flush(&mut self) -> std::io::Result<()> {
-            self.0.as_mut().unwrap().flush()
-        }
-    }
 
-    impl<W: Write> Drop for Lz4Writer<W> {
-        fn drop(&mut self) {
-            if let Some(encoder) = self.0.take() {
-                let (mut w, result) = encoder.finish();
-                if result.is_ok() {
-                    let _ = w.flush();
-                }
-            }
+        fn discard(self: Box<Self>) {
+            self.finish().0.discard();
         }
     }
 }
 
 #[cfg(feature = "xz")]
 pub(crate) mod xz {
+    use super::{invalid_option, WriterOptions};
+    use crate::writer::FinishWrite;
     use crate::OneIoError;
-    use std::fs::File;
-    use std::io::{BufWriter, Read, Write};
+    use std::io::Read;
+    use xz2::stream::{Check, MtStreamBuilder};
+    use xz2::write::XzEncoder;
 
     pub(crate) fn get_reader(
         raw_reader: Box<dyn Read + Send>,
@@ -158,25 +656,99 @@ pub(crate) mod xz {
         Ok(Box::new(xz2::read::XzDecoder::new(raw_reader)))
     }
 
-    pub(crate) fn get_writer(raw_writer: BufWriter<File>) -> Result<Box<dyn Write>, OneIoError> {
-        Ok(Box::new(xz2::write::XzEncoder::new(raw_writer, 6)))
+    /// `LZMA_PRESET_EXTREME` from liblzma.
+    const PRESET_EXTREME: u32 = 1 << 31;
+
+    pub(crate) fn get_writer(
+        raw_writer: Box<dyn FinishWrite>,
+        options: &WriterOptions,
+        threads: usize,
+    ) -> Result<Box<dyn FinishWrite>, OneIoError> {
+        let mut preset = match options.xz_preset {
+            Some(preset) if preset <= 9 => preset,
+            Some(preset) => {
+                return Err(invalid_option(format!(
+                    "xz preset must be in 0..=9, got {preset}"
+                )))
+            }
+            None => options.level_in("xz", 0..=9, 6)? as u32,
+        };
+        if options.xz_extreme {
+            preset |= PRESET_EXTREME;
+        }
+        if threads > 1 {
+            // The multithreaded encoder splits the input into independent
+            // blocks inside a single .xz stream.
+            let stream = MtStreamBuilder::new()
+                .threads(threads.min(u32::MAX as usize) as u32)
+                .preset(preset)
+                .check(Check::Crc64)
+                .encoder()
+                .map_err(std::io::Error::other)?;
+            return Ok(Box::new(XzEncoder::new_stream(raw_writer, stream)));
+        }
+        Ok(Box::new(XzEncoder::new(raw_writer, preset)))
+    }
+
+    impl FinishWrite for XzEncoder<Box<dyn FinishWrite>> {
+        fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+            self.finish()?.finish_write()
+        }
+
+        fn discard(self: Box<Self>) {
+            if let Ok(inner) = self.finish() {
+                inner.discard();
+            }
+        }
     }
 }
 
 #[cfg(feature = "zstd")]
 pub(crate) mod zstd {
+    use super::WriterOptions;
+    use crate::writer::FinishWrite;
     use crate::OneIoError;
-    use std::fs::File;
-    use std::io::{BufWriter, Read, Write};
+    use std::io::Read;
 
     pub(crate) fn get_reader(
         raw_reader: Box<dyn Read + Send>,
+        window_log_max: Option<u32>,
     ) -> Result<Box<dyn Read + Send>, OneIoError> {
-        Ok(Box::new(zstd::Decoder::new(raw_reader)?))
+        let mut decoder = zstd::Decoder::new(raw_reader)?;
+        if let Some(window_log_max) = window_log_max {
+            decoder.window_log_max(window_log_max)?;
+        }
+        Ok(Box::new(decoder))
     }
 
-    pub(crate) fn get_writer(raw_writer: BufWriter<File>) -> Result<Box<dyn Write>, OneIoError> {
-        let encoder = zstd::Encoder::new(raw_writer, 3)?;
-        Ok(Box::new(encoder.auto_finish()))
+    pub(crate) fn get_writer(
+        raw_writer: Box<dyn FinishWrite>,
+        options: &WriterOptions,
+        threads: usize,
+    ) -> Result<Box<dyn FinishWrite>, OneIoError> {
+        let level = options.level_in("zstd", zstd::compression_level_range(), 3)?;
+        let mut encoder = zstd::Encoder::new(raw_writer, level)?;
+        if threads > 1 {
+            encoder.multithread(threads.min(u32::MAX as usize) as u32)?;
+        }
+        if options.zstd_long_distance_matching {
+            encoder.long_distance_matching(true)?;
+        }
+        if let Some(window_log) = options.zstd_window_log {
+            encoder.window_log(window_log)?;
+        }
+        Ok(Box::new(encoder))
+    }
+
+    impl FinishWrite for zstd::Encoder<'static, Box<dyn FinishWrite>> {
+        fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+            self.finish()?.finish_write()
+        }
+
+        fn discard(self: Box<Self>) {
+            if let Ok(inner) = self.finish() {
+                inner.discard();
+            }
+        }
     }
 }
@@ -1,9 +1,384 @@
-//! This module contains functions to calculate the digest of a file.
+//! This module contains functions to calculate the digest of a file and the
+//! [`Checksum`] type used to verify downloads.
 //!
-//! The digest is calculated using the SHA256 algorithm.
+//! Digests can be computed with any [`Algorithm`] over the raw bytes or the
+//! decompressed content ([`DigestMode`]), several algorithms in one pass.
 
+use crate::client::OneIo;
 use crate::OneIoError;
-use ring::digest::{Context, SHA256};
+use md5::Digest;
+use ring::digest::Context;
+use std::io::{Read, Write};
+use std::sync::{Arc, OnceLock};
+
+/// Digest algorithms supported by [`get_digest`] and [`get_digests`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake3,
+    /// CRC-32C (Castagnoli), as used by S3 and GCS object checksums.
+    Crc32c,
+}
+
+impl Algorithm {
+    /// All supported algorithms.
+    pub const ALL: [Algorithm; 6] = [
+        Algorithm::Md5,
+        Algorithm::Sha1,
+        Algorithm::Sha256,
+        Algorithm::Sha512,
+        Algorithm::Blake3,
+        Algorithm::Crc32c,
+    ];
+
+    /// Lowercase name, as accepted by [`FromStr`](std::str::FromStr).
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "md5",
+            Algorithm::Sha1 => "sha1",
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+            Algorithm::Blake3 => "blake3",
+            Algorithm::Crc32c => "crc32c",
+        }
+    }
+
+    /// Length of the hex-encoded digest.
+    pub fn hex_len(self) -> usize {
+        match self {
+            Algorithm::Md5 => 32,
+            Algorithm::Sha1 => 40,
+            Algorithm::Sha256 | Algorithm::Blake3 => 64,
+            Algorithm::Sha512 => 128,
+            Algorithm::Crc32c => 8,
+        }
+    }
+
+    pub(crate) fn hasher(self) -> Hasher {
+        match self {
+            Algorithm::Md5 => Hasher::Md5(md5::Md5::new()),
+            Algorithm::Sha1 => Hasher::Ring(Context::new(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY)),
+            Algorithm::Sha256 => Hasher::Ring(Context::new(&ring::digest::SHA256)),
+            Algorithm::Sha512 => Hasher::Ring(Context::new(&ring::digest::SHA512)),
+            Algorithm::Blake3 => Hasher::Blake3(Box::default()),
+            Algorithm::Crc32c => Hasher::Crc32c(0),
+        }
+    }
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = OneIoError;
+
+    /// Parses an algorithm name case-insensitively, ignoring `-` and `_`
+    /// (`sha256`, `SHA-256` and `sha_256` are equivalent).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        Algorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name() == name)
+            .ok_or_else(|| OneIoError::NotSupported(format!("digest algorithm: {s}")))
+    }
+}
+
+/// Which bytes a digest covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DigestMode {
+    /// The bytes as stored, without decompression.
+    #[default]
+    Raw,
+    /// The content after decompression by file extension, as returned by
+    /// [`get_reader`](crate::get_reader).
+    Decompressed,
+}
+
+/// Expected checksum of a download, for
+/// [`OneIo::download_verified`](crate::OneIo::download_verified).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checksum {
+    /// Hex-encoded SHA-256 digest.
+    Sha256(String),
+    /// Hex-encoded MD5 digest.
+    Md5(String),
+    /// Use the checksum published next to the object: `<url>.sha256`, then
+    /// `<url>.md5`, then the object's line in a `SHA256SUMS` file in the same
+    /// directory.
+    Auto,
+}
+
+/// Incremental state of one [`Algorithm`].
+pub(crate) enum Hasher {
+    Md5(md5::Md5),
+    Ring(Context),
+    Blake3(Box<blake3::Hasher>),
+    Crc32c(u32),
+}
+
+impl Hasher {
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Ring(context) => context.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            Hasher::Crc32c(crc) => *crc = crc32c::crc32c_append(*crc, data),
+        }
+    }
+
+    /// The lowercase hex digest; CRC-32C is rendered big-endian.
+    pub(crate) fn finish_hex(self) -> String {
+        match self {
+            Hasher::Md5(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Ring(context) => hex::encode(context.finish().as_ref()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+            Hasher::Crc32c(crc) => format!("{crc:08x}"),
+        }
+    }
+}
+
+/// Reads `reader` to the end and returns its digests, in the order of
+/// `algorithms`.
+pub(crate) fn digest_reader(
+    reader: &mut dyn Read,
+    algorithms: &[Algorithm],
+) -> Result<Vec<String>, OneIoError> {
+    let mut hashers: Vec<Hasher> = algorithms.iter().map(|a| a.hasher()).collect();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for hasher in &mut hashers {
+            hasher.update(&buffer[..count]);
+        }
+    }
+    Ok(hashers.into_iter().map(Hasher::finish_hex).collect())
+}
+
+/// Reader wrapper that hashes the bytes passing through it.
+///
+/// The digests are published to the reader's [`DigestHandle`] once the inner
+/// reader reaches EOF, so data can be checksummed while it is consumed
+/// instead of being read a second time.
+pub struct HashingReader<R> {
+    inner: R,
+    hashers: Option<Vec<Hasher>>,
+    handle: DigestHandle,
+}
+
+impl<R: Read> HashingReader<R> {
+    /// Wraps `inner`, hashing its bytes with each of `algorithms`.
+    pub fn new(inner: R, algorithms: &[Algorithm]) -> Self {
+        Self {
+            inner,
+            hashers: Some(algorithms.iter().map(|a| a.hasher()).collect()),
+            handle: DigestHandle {
+                algorithms: algorithms.to_vec(),
+                digests: Arc::new(OnceLock::new()),
+            },
+        }
+    }
+
+    /// Returns a handle to the digests, which can outlive the reader.
+    pub fn handle(&self) -> DigestHandle {
+        self.handle.clone()
+    }
+
+    /// Unwraps the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            if let Some(hashers) = &mut self.hashers {
+                for hasher in hashers {
+                    hasher.update(&buf[..n]);
+                }
+            }
+        } else if !buf.is_empty() {
+            if let Some(hashers) = self.hashers.take() {
+                let digests = hashers.into_iter().map(Hasher::finish_hex).collect();
+                let _ = self.handle.digests.set(digests);
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// Shared view of the digests computed by a [`HashingReader`].
+///
+/// Digests are lowercase hex and become available once the reader has
+/// returned EOF; until then every accessor returns `None`.
+#[derive(Debug, Clone)]
+pub struct DigestHandle {
+    algorithms: Vec<Algorithm>,
+    digests: Arc<OnceLock<Vec<String>>>,
+}
+
+impl DigestHandle {
+    /// The algorithms being computed.
+    pub fn algorithms(&self) -> &[Algorithm] {
+        &self.algorithms
+    }
+
+    /// Whether the reader has reached EOF and the digests are final.
+    pub fn is_complete(&self) -> bool {
+        self.digests.get().is_some()
+    }
+
+    /// All digests, in the order of [`algorithms`](Self::algorithms).
+    pub fn digests(&self) -> Option<&[String]> {
+        self.digests.get().map(Vec::as_slice)
+    }
+
+    /// The digest for `algorithm`, if it was requested and EOF was reached.
+    pub fn digest(&self, algorithm: Algorithm) -> Option<&str> {
+        let index = self.algorithms.iter().position(|a| *a == algorithm)?;
+        self.digests().map(|digests| digests[index].as_str())
+    }
+}
+
+/// Digest handles returned by
+/// [`OneIo::get_reader_with_digest`](crate::OneIo::get_reader_with_digest).
+#[derive(Debug, Clone)]
+pub struct ReaderDigests {
+    /// Digests of the raw bytes, as stored.
+    pub raw: DigestHandle,
+    /// Digests of the decompressed bytes returned by the reader.
+    pub decompressed: DigestHandle,
+}
+
+/// Resolves `checksum` for `remote` into its algorithm and the expected
+/// lowercase hex digest, fetching published checksum files for
+/// [`Checksum::Auto`].
+pub(crate) fn expected_digest(
+    oneio: &OneIo,
+    remote: &str,
+    checksum: Checksum,
+) -> Result<(Algorithm, String), OneIoError> {
+    let (algorithm, expected) = match checksum {
+        Checksum::Sha256(expected) => (Algorithm::Sha256, expected),
+        Checksum::Md5(expected) => (Algorithm::Md5, expected),
+        Checksum::Auto => return discover_checksum(oneio, remote),
+    };
+    let expected = expected.trim().to_ascii_lowercase();
+    if !is_hex_digest(&expected, algorithm.hex_len()) {
+        return Err(OneIoError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid {algorithm} digest: {expected}"),
+        )));
+    }
+    Ok((algorithm, expected))
+}
+
+/// Looks for `<url>.sha256`, `<url>.md5` and `SHA256SUMS` next to `remote`.
+fn discover_checksum(oneio: &OneIo, remote: &str) -> Result<(Algorithm, String), OneIoError> {
+    let (object, query) = match remote.split_once('?') {
+        Some((object, query)) => (object, format!("?{query}")),
+        None => (remote, String::new()),
+    };
+    let (dir, file_name) = object.rsplit_once('/').unwrap_or(("", object));
+
+    if let Some(digest) = fetch_small(oneio, &format!("{object}.sha256{query}"))
+        .and_then(|text| first_digest(&text, 64))
+    {
+        return Ok((Algorithm::Sha256, digest));
+    }
+    if let Some(digest) =
+        fetch_small(oneio, &format!("{object}.md5{query}")).and_then(|text| first_digest(&text, 32))
+    {
+        return Ok((Algorithm::Md5, digest));
+    }
+    if let Some(digest) = fetch_small(oneio, &format!("{dir}/SHA256SUMS{query}"))
+        .and_then(|text| sums_digest(&text, file_name))
+    {
+        return Ok((Algorithm::Sha256, digest));
+    }
+    Err(OneIoError::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("no {object}.sha256, {object}.md5 or SHA256SUMS entry found"),
+    )))
+}
+
+/// Reads a checksum file of at most 1 MiB; missing or unreadable files are
+/// `None`.
+fn fetch_small(oneio: &OneIo, path: &str) -> Option<String> {
+    let mut text = String::new();
+    oneio
+        .get_reader_raw(path)
+        .ok()?
+        .take(1 << 20)
+        .read_to_string(&mut text)
+        .ok()?;
+    Some(text)
+}
+
+/// The first whitespace-separated token of `text` that is a `hex_len` digit
+/// hex digest, as in `sha256sum` output or a bare digest.
+fn first_digest(text: &str, hex_len: usize) -> Option<String> {
+    text.split_whitespace()
+        .map(str::to_ascii_lowercase)
+        .find(|token| is_hex_digest(token, hex_len))
+}
+
+/// The digest of `file_name` in `sha256sum` output (`<hex>  <name>`, or
+/// `<hex> *<name>` for binary mode).
+fn sums_digest(text: &str, file_name: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (digest, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start().trim_start_matches('*');
+        let name = name.strip_prefix("./").unwrap_or(name);
+        let digest = digest.to_ascii_lowercase();
+        (name == file_name && is_hex_digest(&digest, 64)).then_some(digest)
+    })
+}
+
+fn is_hex_digest(s: &str, hex_len: usize) -> bool {
+    s.len() == hex_len && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Copies `reader` to `writer` while feeding every byte to `hasher`.
+pub(crate) fn copy_hashing(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    algorithm: Algorithm,
+) -> Result<String, OneIoError> {
+    let mut hasher = algorithm.hasher();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.update(&buffer[..count]);
+        writer.write_all(&buffer[..count])?;
+    }
+    writer.flush()?;
+    Ok(hasher.finish_hex())
+}
 
 /// Calculate the SHA256 digest of a file.
 ///
@@ -11,7 +386,8 @@ use ring::digest::{Context, SHA256};
 /// as a hexadecimal string. Supports both local files and remote URLs (HTTP, HTTPS, FTP, S3).
 ///
 /// Note: This function computes the hash of the raw file bytes without any decompression,
-/// even if the file has a compression extension (e.g., `.gz`, `.bz2`).
+/// even if the file has a compression extension (e.g., `.gz`, `.bz2`). Use [`get_digest`]
+/// for other algorithms or the decompressed content.
 ///
 /// # Arguments
 ///
@@ -21,20 +397,93 @@ use ring::digest::{Context, SHA256};
 ///
 /// Returns the SHA256 digest as a hexadecimal string, or an error if the file cannot be read.
 pub fn get_sha256_digest(path: &str) -> Result<String, OneIoError> {
-    let mut context = Context::new(&SHA256);
-    let mut buffer = [0; 1024];
+    get_digest(path, Algorithm::Sha256, DigestMode::Raw)
+}
 
-    // Open file for reading (supports both local and remote files, no decompression)
-    let mut reader = crate::OneIo::new()?.get_reader_raw(path)?;
+/// Calculate the digest of a local or remote file with `algorithm`.
+///
+/// See [`OneIo::get_digest`] for details.
+pub fn get_digest(
+    path: &str,
+    algorithm: Algorithm,
+    mode: DigestMode,
+) -> Result<String, OneIoError> {
+    crate::builder::default_oneio()?.get_digest(path, algorithm, mode)
+}
 
-    loop {
-        let count = reader.read(&mut buffer)?;
-        if count == 0 {
-            break;
+/// Calculate several digests of a local or remote file in a single pass.
+///
+/// See [`OneIo::get_digests`] for details.
+pub fn get_digests(
+    path: &str,
+    algorithms: &[Algorithm],
+    mode: DigestMode,
+) -> Result<Vec<String>, OneIoError> {
+    crate::builder::default_oneio()?.get_digests(path, algorithms, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn test_parse_checksum_files() {
+        assert_eq!(
+            first_digest(&format!("{}  rib.gz\n", DIGEST.to_uppercase()), 64).as_deref(),
+            Some(DIGEST)
+        );
+        assert_eq!(first_digest(DIGEST, 64).as_deref(), Some(DIGEST));
+        assert_eq!(first_digest("not a digest", 64), None);
+
+        let sums = format!("{}  other.gz\n{DIGEST} *./rib.gz\n", "0".repeat(64));
+        assert_eq!(sums_digest(&sums, "rib.gz").as_deref(), Some(DIGEST));
+        assert_eq!(sums_digest(&sums, "missing.gz"), None);
+    }
+
+    #[test]
+    fn test_hashing_reader_publishes_at_eof() {
+        let mut reader = HashingReader::new("hello\n".as_bytes(), &[Algorithm::Md5]);
+        let handle = reader.handle();
+        let mut buf = [0u8; 3];
+        reader.read_exact(&mut buf).unwrap();
+        assert!(!handle.is_complete());
+        assert_eq!(handle.digest(Algorithm::Md5), None);
+
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+        assert_eq!(
+            handle.digest(Algorithm::Md5),
+            Some("b1946ac92492d2347c6235b4d2611184")
+        );
+        assert_eq!(handle.digest(Algorithm::Sha256), None);
+    }
+
+    #[test]
+    fn test_algorithm_names() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.name().parse::<Algorithm>().unwrap(), algorithm);
         }
-        context.update(&buffer[..count]);
+        assert_eq!("SHA-256".parse::<Algorithm>().unwrap(), Algorithm::Sha256);
+        assert!("sha3".parse::<Algorithm>().is_err());
     }
 
-    let digest = context.finish();
-    Ok(hex::encode(digest.as_ref()))
+    #[test]
+    fn test_known_digests() {
+        let digests = digest_reader(&mut "hello\n".as_bytes(), &Algorithm::ALL).unwrap();
+        assert_eq!(
+            digests,
+            [
+                "b1946ac92492d2347c6235b4d2611184",
+                "f572d396fae9206628714fb2ce00f72e94f2258f",
+                "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03",
+                "e7c22b994c59d9cf2b48e549b1e24666636045930d3da7c1acb299d1c3b7f931f94aae41edda2c2b207a36e10f8bcb8d45223e54878f5b316e7ce3b6bc019629",
+                "8e4c7c1b99dbfd50e7a95185fead5ee1448fa904a2fdd778eaf5f2dbfd629a99",
+                "353dd8be",
+            ]
+        );
+        for (algorithm, digest) in Algorithm::ALL.iter().zip(&digests) {
+            assert_eq!(digest.len(), algorithm.hex_len());
+        }
+    }
 }
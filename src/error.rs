@@ -38,6 +38,19 @@ pub enum OneIoError {
     /// Feature not supported/compiled
     #[error("Not supported: {0}")]
     NotSupported(String),
+
+    /// The backend or server cannot serve byte ranges for this path
+    #[error("Range requests not supported: {0}")]
+    RangeNotSupported(String),
+
+    /// Downloaded data does not match its expected checksum
+    #[error("{algorithm} checksum mismatch for {path}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        path: String,
+        algorithm: &'static str,
+        expected: String,
+        actual: String,
+    },
 }
 
 // Convert various network-related errors to Network variant
@@ -55,6 +68,19 @@ impl From<suppaftp::FtpError> for OneIoError {
     }
 }
 
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for OneIoError {
+    fn from(err: zip::result::ZipError) -> Self {
+        match err {
+            zip::result::ZipError::Io(e) => OneIoError::Io(e),
+            zip::result::ZipError::FileNotFound => {
+                OneIoError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, err))
+            }
+            err => OneIoError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err)),
+        }
+    }
+}
+
 #[cfg(feature = "json")]
 impl From<serde_json::Error> for OneIoError {
     fn from(err: serde_json::Error) -> Self {
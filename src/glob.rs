@@ -0,0 +1,313 @@
+//! Glob expansion over local directories and remote prefixes.
+//!
+//! `*` matches any characters within one path segment, `?` exactly one
+//! character, `**` any number of whole segments (including none) and
+//! `{a,b}` either alternative. Braces may nest. Local patterns are expanded
+//! by walking only the directories the pattern can reach. Remote patterns
+//! are walked the same way through the [`Backend::list`] of the client's
+//! backend for the scheme, starting at the literal prefix before the first
+//! wildcard segment and filtering each listing client-side.
+
+use crate::backend::{Backend, ListEntry};
+use crate::client::OneIo;
+use crate::OneIoError;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Expands `pattern` into the sorted, de-duplicated list of matching paths.
+pub(crate) fn expand(oneio: &OneIo, pattern: &str) -> Result<Vec<String>, OneIoError> {
+    let patterns = expand_braces(pattern)?;
+    let mut matches = BTreeSet::new();
+    // Brace alternatives often share directories; list each one only once.
+    let mut listings: HashMap<String, Vec<ListEntry>> = HashMap::new();
+
+    for pattern in &patterns {
+        match crate::get_protocol(pattern) {
+            None | Some("file") => {
+                let (scheme, path) = match pattern.strip_prefix("file://") {
+                    Some(path) => ("file://", path),
+                    None => ("", pattern.as_str()),
+                };
+                let (root, rest) = match path.strip_prefix('/') {
+                    Some(rest) => ("/", rest),
+                    None => ("", path),
+                };
+                let components: Vec<&str> = rest.split('/').filter(|c| !c.is_empty()).collect();
+                let mut local = BTreeSet::new();
+                walk_local(root, &components, &mut local)?;
+                matches.extend(local.into_iter().map(|path| format!("{scheme}{path}")));
+            }
+            Some(_) => {
+                let backend = oneio.backend(pattern)?;
+                let (scheme, rest) = pattern.split_once("://").unwrap_or_default();
+                let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+                if has_wildcards(authority) {
+                    return Err(OneIoError::NotSupported(format!(
+                        "glob: wildcards in bucket or host names: {pattern}"
+                    )));
+                }
+                let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+                if components.is_empty() {
+                    continue;
+                }
+                // List from the deepest directory named literally, so that
+                // even a pattern without wildcards is checked against a listing.
+                let literal = components
+                    .iter()
+                    .position(|c| has_wildcards(c) || *c == "**")
+                    .unwrap_or(components.len() - 1);
+                let root = components[..literal]
+                    .iter()
+                    .fold(format!("{scheme}://{authority}"), |dir, c| join(&dir, c));
+                walk_remote(
+                    backend.as_ref(),
+                    &root,
+                    &components[literal..],
+                    &mut listings,
+                    &mut matches,
+                )?;
+            }
+        }
+    }
+    Ok(matches.into_iter().collect())
+}
+
+/// Expands `{a,b}` alternatives left to right, including nested braces.
+fn expand_braces(pattern: &str) -> Result<Vec<String>, OneIoError> {
+    let Some(open) = pattern.find('{') else {
+        if pattern.contains('}') {
+            return Err(unbalanced(pattern));
+        }
+        return Ok(vec![pattern.to_string()]);
+    };
+
+    let mut depth = 0;
+    let mut alternatives = Vec::new();
+    let mut start = open + 1;
+    for (i, c) in pattern[open..].char_indices().map(|(i, c)| (open + i, c)) {
+        match c {
+            '{' => depth += 1,
+            ',' if depth == 1 => {
+                alternatives.push(&pattern[start..i]);
+                start = i + 1;
+            }
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    alternatives.push(&pattern[start..i]);
+                    let (head, tail) = (&pattern[..open], &pattern[i + 1..]);
+                    let mut expanded = Vec::new();
+                    for alternative in alternatives {
+                        expanded.extend(expand_braces(&format!("{head}{alternative}{tail}"))?);
+                    }
+                    return Ok(expanded);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(unbalanced(pattern))
+}
+
+fn unbalanced(pattern: &str) -> OneIoError {
+    OneIoError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("unbalanced braces in glob pattern: {pattern}"),
+    ))
+}
+
+/// Whether `s` contains `*` or `?`.
+fn has_wildcards(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+/// Matches one path segment against `*` and `?` wildcards.
+fn match_segment(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it currently absorbs up to.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, absorbed)) => {
+                    p = star + 1;
+                    n = absorbed + 1;
+                    backtrack = Some((star, n));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Collects local paths below `dir` matching the remaining pattern `components`.
+fn walk_local(
+    dir: &str,
+    components: &[&str],
+    matches: &mut BTreeSet<String>,
+) -> Result<(), OneIoError> {
+    let Some((&first, rest)) = components.split_first() else {
+        if !dir.is_empty() && Path::new(dir).exists() {
+            matches.insert(dir.to_string());
+        }
+        return Ok(());
+    };
+
+    if first == "**" {
+        if rest.is_empty() {
+            // A trailing `**` matches everything beneath `dir`.
+            return walk_local(dir, &["**", "*"], matches);
+        }
+        walk_local(dir, rest, matches)?;
+        // Symlinked directories are not followed, so cycles cannot recurse.
+        for (name, file_type) in read_dir(dir)? {
+            if file_type.is_dir() {
+                walk_local(&join(dir, &name), components, matches)?;
+            }
+        }
+        return Ok(());
+    }
+
+    if !has_wildcards(first) {
+        let path = join(dir, first);
+        if rest.is_empty() || Path::new(&path).is_dir() {
+            walk_local(&path, rest, matches)?;
+        }
+        return Ok(());
+    }
+
+    for (name, _) in read_dir(dir)? {
+        if match_segment(first, &name) {
+            let path = join(dir, &name);
+            if rest.is_empty() || Path::new(&path).is_dir() {
+                walk_local(&path, rest, matches)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collects remote paths below `dir` matching the remaining pattern
+/// `components`, listing each directory through `backend` at most once.
+fn walk_remote(
+    backend: &dyn Backend,
+    dir: &str,
+    components: &[&str],
+    listings: &mut HashMap<String, Vec<ListEntry>>,
+    matches: &mut BTreeSet<String>,
+) -> Result<(), OneIoError> {
+    let Some((&first, rest)) = components.split_first() else {
+        matches.insert(dir.to_string());
+        return Ok(());
+    };
+
+    if first == "**" {
+        if rest.is_empty() {
+            // A trailing `**` matches everything beneath `dir`.
+            return walk_remote(backend, dir, &["**", "*"], listings, matches);
+        }
+        walk_remote(backend, dir, rest, listings, matches)?;
+        for (name, is_dir) in list_remote(backend, dir, listings)? {
+            if is_dir {
+                walk_remote(backend, &join(dir, &name), components, listings, matches)?;
+            }
+        }
+        return Ok(());
+    }
+
+    for (name, is_dir) in list_remote(backend, dir, listings)? {
+        if match_segment(first, &name) && (rest.is_empty() || is_dir) {
+            walk_remote(backend, &join(dir, &name), rest, listings, matches)?;
+        }
+    }
+    Ok(())
+}
+
+/// Lists the entry names of the remote `dir` and whether they are
+/// directories; a missing directory has no entries.
+fn list_remote(
+    backend: &dyn Backend,
+    dir: &str,
+    listings: &mut HashMap<String, Vec<ListEntry>>,
+) -> Result<Vec<(String, bool)>, OneIoError> {
+    if !listings.contains_key(dir) {
+        let entries = match backend.list(dir) {
+            Ok(entries) => entries,
+            Err(OneIoError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        listings.insert(dir.to_string(), entries);
+    }
+    Ok(listings[dir]
+        .iter()
+        .map(|entry| (entry.name.clone(), entry.is_dir))
+        .collect())
+}
+
+/// Lists the UTF-8 entry names of `dir`; a missing directory has no entries.
+fn read_dir(dir: &str) -> Result<Vec<(String, std::fs::FileType)>, OneIoError> {
+    let entries = match std::fs::read_dir(if dir.is_empty() { "." } else { dir }) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if let Ok(name) = entry.file_name().into_string() {
+            names.push((name, entry.file_type()?));
+        }
+    }
+    Ok(names)
+}
+
+fn join(dir: &str, name: &str) -> String {
+    match dir {
+        "" => name.to_string(),
+        dir if dir.ends_with('/') => format!("{dir}{name}"),
+        dir => format!("{dir}/{name}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(
+            expand_braces("s3://b/{2023,2024}/{rib,update{s,}}.gz").unwrap(),
+            [
+                "s3://b/2023/rib.gz",
+                "s3://b/2023/updates.gz",
+                "s3://b/2023/update.gz",
+                "s3://b/2024/rib.gz",
+                "s3://b/2024/updates.gz",
+                "s3://b/2024/update.gz",
+            ]
+        );
+        assert_eq!(expand_braces("plain/*.gz").unwrap(), ["plain/*.gz"]);
+        assert!(expand_braces("a/{b,c").is_err());
+        assert!(expand_braces("a/b}").is_err());
+    }
+
+    #[test]
+    fn test_match_segment() {
+        assert!(match_segment("updates.*.gz", "updates.0000.gz"));
+        assert!(match_segment("rib.????.bz2", "rib.0800.bz2"));
+        assert!(!match_segment("rib.????.bz2", "rib.08000.bz2"));
+        assert!(match_segment("*a*b*", "xxaybzb"));
+        assert!(!match_segment("*.gz", "data.gz.tmp"));
+    }
+}
@@ -32,13 +32,15 @@ Enable only what you need:
 | `lz` | LZ4 compression |
 | `xz` | XZ compression |
 | `zstd` | Zstandard compression |
+| `archive` | Tar archive members (`archive.tar.gz!/member`) |
+| `zip` | Zip archive members (`archive.zip!/member`) |
 | `http` | HTTP/HTTPS support |
 | `reqwest-gzip` | Opt-in HTTP gzip content-encoding (advertises `Accept-Encoding: gzip`, transparently decodes responses) |
 | `ftp` | FTP support |
 | `s3` | S3-compatible storage |
 | `async` | Async I/O support |
 | `json` | JSON deserialization |
-| `digest` | SHA256 hashing |
+| `digest` | MD5, SHA-1, SHA-2, BLAKE3 and CRC32C digests and checksum-verified downloads |
 | `cli` | Command-line tool |
 
 **Example: Minimal setup for local files**
@@ -81,7 +83,8 @@ use std::io::Write;
 
 let mut writer = oneio::get_writer("output.txt.gz")?;
 writer.write_all(b"Hello")?;
-// Compression finalized on drop
+// Finalize compression and report any error (dropping finalizes plain files silently)
+writer.finish()?;
 # Ok(())
 # }
 ```
@@ -135,6 +138,20 @@ let reader = client.get_reader_with_type(
 # }
 ```
 
+Or detect the codec from the leading magic bytes:
+
+```rust,no_run
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+use oneio::{CompressionDetection, OneIo};
+
+let client = OneIo::builder()
+    .compression_detection(CompressionDetection::SniffWithExtensionFallback)
+    .build()?;
+let content = client.read_to_string_lossy("https://example.com/download?id=123")?;
+# Ok(())
+# }
+```
+
 # Protocols
 
 - **Local**: `/path/to/file.txt`
@@ -142,6 +159,15 @@ let reader = client.get_reader_with_type(
 - **FTP**: `ftp://ftp.example.com/file.txt` (requires `ftp` feature)
 - **S3**: `s3://bucket/key` (requires `s3` feature)
 
+Each scheme is served by a [`Backend`]. Register your own with
+[`OneIoBuilder::backend`] to add a scheme (or replace a built-in one); custom
+backends get decompression, caching and progress tracking for free:
+
+```rust,ignore
+let client = OneIo::builder().backend("mem", MyBackend).build()?;
+let content = client.read_to_string_lossy("mem://data.txt.gz")?;
+```
+
 # Async API
 
 Enable the `async` feature:
@@ -232,17 +258,37 @@ For environments with custom TLS certificates (Cloudflare WARP, corporate proxie
     html_favicon_url = "https://raw.githubusercontent.com/bgpkit/assets/main/logos/favicon.ico"
 )]
 
+mod archive;
+mod atomic;
+mod backend;
 mod builder;
+mod cache;
 mod client;
 mod compression;
 mod error;
+mod glob;
+mod listing;
 mod progress;
+mod rate_limit;
 #[cfg(feature = "http")]
 mod resumable_http;
-
+mod seekable;
+#[cfg(feature = "archive")]
+mod tar_archive;
+mod writer;
+#[cfg(feature = "zip")]
+mod zip_archive;
+
+pub use archive::{ArchiveEntry, ArchiveEntryKind};
+pub use backend::{Backend, ChangedObject, ListEntry, ObjectStat};
 pub use builder::OneIoBuilder;
+pub use cache::{CacheStats, OneIoCache};
 pub use client::OneIo;
+pub use compression::{CompressionDetection, Lz4BlockSize, WriterOptions};
 pub use error::OneIoError;
+pub use progress::{ProgressEvent, ProgressOptions};
+pub use seekable::SeekableReader;
+pub use writer::OneIoWriter;
 
 /// Re-export of the exact `reqwest` crate oneio is built against.
 ///
@@ -306,7 +352,7 @@ pub use s3::*;
 pub use digest::*;
 
 use std::fs::File;
-use std::io::{BufWriter, Read, Write};
+use std::io::{BufWriter, Read};
 
 // Internal helpers
 
@@ -356,11 +402,41 @@ pub fn get_resumable_http_reader(path: &str) -> Result<Box<dyn Read + Send>, One
     builder::default_oneio()?.get_resumable_http_reader(path)
 }
 
+/// Opens a local or remote file for random access, without decompression.
+///
+/// See [`OneIo::get_seekable_reader`] for details.
+pub fn get_seekable_reader(path: &str) -> Result<SeekableReader, OneIoError> {
+    builder::default_oneio()?.get_seekable_reader(path)
+}
+
+/// Reads `len` raw bytes of a local or remote file starting at `offset`.
+///
+/// See [`OneIo::read_range`] for details.
+pub fn read_range(path: &str, offset: u64, len: u64) -> Result<Vec<u8>, OneIoError> {
+    builder::default_oneio()?.read_range(path, offset, len)
+}
+
+/// Lists the members of a local or remote tar or zip archive.
+///
+/// See [`OneIo::list_archive`] for details.
+pub fn list_archive(
+    path: &str,
+) -> Result<impl Iterator<Item = Result<ArchiveEntry, OneIoError>> + Send, OneIoError> {
+    builder::default_oneio()?.list_archive(path)
+}
+
 /// Returns a writer for the given file path with the corresponding compression.
-pub fn get_writer(path: &str) -> Result<Box<dyn Write>, OneIoError> {
+pub fn get_writer(path: &str) -> Result<OneIoWriter, OneIoError> {
     builder::default_oneio()?.get_writer(path)
 }
 
+/// Lists the entries directly under a local directory or remote prefix.
+///
+/// See [`OneIo::list`] for details.
+pub fn list(path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+    builder::default_oneio()?.list(path)
+}
+
 /// Checks whether a local or remote path exists.
 pub fn exists(path: &str) -> Result<bool, OneIoError> {
     builder::default_oneio()?.exists(path)
@@ -410,6 +486,30 @@ pub fn read_lines_lossy(
     builder::default_oneio()?.read_lines_lossy(path)
 }
 
+/// Expands a glob pattern over local directories or remote prefixes.
+///
+/// See [`OneIo::glob`] for the supported syntax.
+pub fn glob(pattern: &str) -> Result<Vec<String>, OneIoError> {
+    builder::default_oneio()?.glob(pattern)
+}
+
+/// Streams the lines of every file matching a glob pattern, in sorted order.
+///
+/// See [`OneIo::read_lines_lossy_glob`] for details.
+pub fn read_lines_lossy_glob(
+    pattern: &str,
+) -> Result<impl Iterator<Item = std::io::Result<String>> + Send, OneIoError> {
+    builder::default_oneio()?.read_lines_lossy_glob(pattern)
+}
+
+/// Downloads a remote resource to a local path and verifies its checksum.
+///
+/// See [`OneIo::download_verified`] for details.
+#[cfg(feature = "digest")]
+pub fn download_verified(remote: &str, local: &str, checksum: Checksum) -> Result<(), OneIoError> {
+    builder::default_oneio()?.download_verified(remote, local, checksum)
+}
+
 /// Downloads a remote resource to a local path.
 pub fn download(remote: &str, local: &str) -> Result<(), OneIoError> {
     builder::default_oneio()?.download(remote, local)
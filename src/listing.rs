@@ -0,0 +1,444 @@
+//! Directory listing helpers shared by the built-in backends.
+//!
+//! Modification times of all backends are formatted as RFC 3339 UTC
+//! timestamps (`2024-01-31T23:00:00Z`). HTTP listings are scraped from the
+//! autoindex pages generated by Apache (`mod_autoindex`, both `<pre>` and
+//! table layouts), nginx (`autoindex on`) and lighttpd (`dir-listing`).
+
+#[cfg(feature = "http")]
+use crate::backend::ListEntry;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Formats `time` as `YYYY-MM-DDTHH:MM:SSZ`, or `None` before the Unix epoch.
+pub(crate) fn format_system_time(time: SystemTime) -> Option<String> {
+    let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    Some(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    ))
+}
+
+/// Drops the fractional seconds of an RFC 3339 UTC timestamp, turning the
+/// `2024-01-31T23:00:00.000Z` of S3 listings into `2024-01-31T23:00:00Z`.
+#[cfg(feature = "s3")]
+pub(crate) fn trim_fractional_seconds(time: &str) -> String {
+    match time.split_once('.') {
+        Some((seconds, fraction)) if fraction.ends_with('Z') => format!("{seconds}Z"),
+        _ => time.to_string(),
+    }
+}
+
+/// Converts days since 1970-01-01 into a proleptic Gregorian date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Extracts the entries directly under `base` from an HTML index page.
+///
+/// Every link resolving to a direct child of `base` becomes an entry; links
+/// to parent directories, other hosts and sort controls (`?C=N;O=D`) are
+/// skipped. The date and size columns following a link are parsed when
+/// present. Index pages carry no time zone, so dates are read as UTC (the
+/// nginx default) and formatted like [`format_system_time`]. Abbreviated
+/// sizes such as `1.2M` are converted to approximate byte counts.
+#[cfg(feature = "http")]
+pub(crate) fn parse_http_index(base: &reqwest::Url, html: &str) -> Vec<ListEntry> {
+    // ASCII lowercasing keeps byte offsets identical to `html`.
+    let lower = html.to_ascii_lowercase();
+    let mut entries: Vec<ListEntry> = Vec::new();
+    let mut seen = std::collections::HashMap::new();
+
+    let mut pos = 0;
+    while let Some(start) = find_anchor(&lower, pos) {
+        let Some(tag_end) = lower[start..].find('>').map(|i| start + i) else {
+            break;
+        };
+        let Some(close) = lower[tag_end..].find("</a>").map(|i| tag_end + i) else {
+            break;
+        };
+        pos = close + "</a>".len();
+
+        let Some(href) = attribute(&html[start..tag_end], &lower[start..tag_end], "href") else {
+            continue;
+        };
+        let Some((name, url, is_dir)) = child_entry(base, &href) else {
+            continue;
+        };
+
+        // Date and size columns run until the next link, row or line.
+        let rest = &lower[pos..];
+        let details_end = [find_anchor(rest, 0), rest.find("</tr"), rest.find('\n')]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(rest.len());
+        let (last_modified, size) = parse_details(&html[pos..pos + details_end]);
+
+        match seen.get(&url) {
+            // Icon links repeat the name link; keep whichever carries details.
+            Some(&index) => {
+                let entry: &mut ListEntry = &mut entries[index];
+                entry.last_modified = entry.last_modified.take().or(last_modified);
+                entry.size = entry.size.or(size);
+            }
+            None => {
+                seen.insert(url.clone(), entries.len());
+                entries.push(ListEntry {
+                    name,
+                    path: url,
+                    size: if is_dir { None } else { size },
+                    last_modified,
+                    is_dir,
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// Finds the next `<a` tag at or after `from` in lowercased HTML.
+#[cfg(feature = "http")]
+fn find_anchor(lower: &str, from: usize) -> Option<usize> {
+    let mut pos = from;
+    while let Some(i) = lower[pos..].find("<a") {
+        let start = pos + i;
+        if lower[start + 2..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_whitespace())
+        {
+            return Some(start);
+        }
+        pos = start + 2;
+    }
+    None
+}
+
+/// Returns the unescaped value of attribute `name` in an opening tag.
+#[cfg(feature = "http")]
+fn attribute(tag: &str, lower_tag: &str, name: &str) -> Option<String> {
+    let mut pos = 0;
+    while let Some(i) = lower_tag[pos..].find(name) {
+        let start = pos + i;
+        pos = start + name.len();
+        let preceded_by_space = lower_tag[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_whitespace());
+        let rest = lower_tag[pos..].trim_start();
+        if !preceded_by_space || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = tag.len() - rest[1..].trim_start().len();
+        let value = &tag[value_start..];
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value
+                .split(|c: char| c.is_ascii_whitespace() || c == '>')
+                .next()
+                .unwrap_or_default(),
+        };
+        return Some(unescape_html(value));
+    }
+    None
+}
+
+/// Resolves `href` against `base` and keeps it only if it names a direct
+/// child of `base`. Returns the decoded name, the URL and whether it is a
+/// directory.
+#[cfg(feature = "http")]
+fn child_entry(base: &reqwest::Url, href: &str) -> Option<(String, String, bool)> {
+    if href.starts_with(['?', '#']) {
+        return None;
+    }
+    let url = base.join(href).ok()?;
+    if url.origin() != base.origin() || url.query().is_some() || url.fragment().is_some() {
+        return None;
+    }
+    let rest = url.path().strip_prefix(base.path())?;
+    let (segment, is_dir) = match rest.strip_suffix('/') {
+        Some(segment) => (segment, true),
+        None => (rest, false),
+    };
+    if segment.is_empty() || segment.contains('/') {
+        return None;
+    }
+    Some((percent_decode(segment), url.to_string(), is_dir))
+}
+
+/// Parses the date and size columns that follow a link.
+#[cfg(feature = "http")]
+fn parse_details(html: &str) -> (Option<String>, Option<u64>) {
+    let text = strip_tags(html);
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    for (i, pair) in tokens.windows(2).enumerate() {
+        let (Some(date), true) = (parse_date(pair[0]), is_time(pair[1])) else {
+            continue;
+        };
+        let size = tokens.get(i + 2).and_then(|token| parse_size(token));
+        let time = match pair[1].len() {
+            5 => format!("{}:00", pair[1]),
+            _ => pair[1].to_string(),
+        };
+        return (Some(format!("{date}T{time}Z")), size);
+    }
+    (None, None)
+}
+
+/// Normalizes `2024-01-31`, `31-Jan-2024` and `2024-Jan-31` to `2024-01-31`.
+#[cfg(feature = "http")]
+fn parse_date(token: &str) -> Option<String> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let month_number = |month: &str| -> Option<usize> {
+        match month.parse::<usize>() {
+            Ok(month) => (1..=12).contains(&month).then_some(month),
+            Err(_) => MONTHS
+                .iter()
+                .position(|m| m.eq_ignore_ascii_case(month))
+                .map(|i| i + 1),
+        }
+    };
+    let day_number = |day: &str| -> Option<usize> {
+        let number = day.parse::<usize>().ok()?;
+        (day.len() <= 2 && (1..=31).contains(&number)).then_some(number)
+    };
+
+    let parts: Vec<&str> = token.split('-').collect();
+    let [a, b, c] = parts.as_slice() else {
+        return None;
+    };
+    let (year, month, day) = if a.len() == 4 {
+        (*a, month_number(b)?, day_number(c)?)
+    } else if c.len() == 4 {
+        (*c, month_number(b)?, day_number(a)?)
+    } else {
+        return None;
+    };
+    if !year.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{year}-{month:02}-{day:02}"))
+}
+
+/// Whether `token` looks like `HH:MM` or `HH:MM:SS`.
+#[cfg(feature = "http")]
+fn is_time(token: &str) -> bool {
+    let parts: Vec<&str> = token.split(':').collect();
+    (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| part.len() == 2 && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Parses an exact (`12345`) or abbreviated (`1.2M`, `12K`) size column.
+#[cfg(feature = "http")]
+fn parse_size(token: &str) -> Option<u64> {
+    if let Ok(size) = token.parse::<u64>() {
+        return Some(size);
+    }
+    let token = token.trim_end_matches(['B', 'b']).trim_end_matches('i');
+    let (unit_start, _) = token.char_indices().next_back()?;
+    let (number, unit) = token.split_at(unit_start);
+    let exponent = match unit.to_ascii_uppercase().as_str() {
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => return None,
+    };
+    let number: f64 = number.parse().ok()?;
+    (number >= 0.0).then(|| (number * 1024_f64.powi(exponent)).round() as u64)
+}
+
+/// Removes tags and decodes entities, keeping the text content.
+#[cfg(feature = "http")]
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    unescape_html(&text)
+}
+
+#[cfg(feature = "http")]
+fn unescape_html(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Decodes `%XX` escapes, replacing invalid UTF-8 with `U+FFFD`.
+#[cfg(feature = "http")]
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_system_time() {
+        assert_eq!(
+            format_system_time(UNIX_EPOCH).as_deref(),
+            Some("1970-01-01T00:00:00Z")
+        );
+        // 2024-02-29T12:34:56Z, a leap day.
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(
+            format_system_time(time).as_deref(),
+            Some("2024-02-29T12:34:56Z")
+        );
+    }
+
+    #[cfg(feature = "s3")]
+    #[test]
+    fn test_trim_fractional_seconds() {
+        assert_eq!(
+            trim_fractional_seconds("2024-01-31T23:00:00.000Z"),
+            "2024-01-31T23:00:00Z"
+        );
+        assert_eq!(
+            trim_fractional_seconds("2024-01-31T23:00:00Z"),
+            "2024-01-31T23:00:00Z"
+        );
+    }
+
+    #[cfg(feature = "http")]
+    fn base() -> reqwest::Url {
+        reqwest::Url::parse("https://archive.example.org/bgpdata/2024.01/").unwrap()
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_parse_apache_table_index() {
+        let html = r#"<html><body><h1>Index of /bgpdata/2024.01</h1>
+<table>
+   <tr><th valign="top"><img src="/icons/blank.gif" alt="[ICO]"></th><th><a href="?C=N;O=D">Name</a></th><th><a href="?C=M;O=A">Last modified</a></th><th><a href="?C=S;O=A">Size</a></th></tr>
+<tr><td valign="top"><img src="/icons/back.gif" alt="[PARENTDIR]"></td><td><a href="/bgpdata/">Parent Directory</a></td><td>&nbsp;</td><td align="right">  - </td></tr>
+<tr><td valign="top"><img src="/icons/folder.gif" alt="[DIR]"></td><td><a href="RIBS/">RIBS/</a></td><td align="right">2024-01-31 23:00  </td><td align="right">  - </td></tr>
+<tr><td valign="top"><img src="/icons/compressed.gif" alt="[   ]"></td><td><a href="updates.20240101.0000.bz2">updates.20240101.0000.bz2</a></td><td align="right">2024-01-01 00:15  </td><td align="right">1.2M</td></tr>
+</table></body></html>"#;
+        let entries = parse_http_index(&base(), html);
+        assert_eq!(
+            entries,
+            [
+                ListEntry {
+                    name: "RIBS".to_string(),
+                    path: "https://archive.example.org/bgpdata/2024.01/RIBS/".to_string(),
+                    size: None,
+                    last_modified: Some("2024-01-31T23:00:00Z".to_string()),
+                    is_dir: true,
+                },
+                ListEntry {
+                    name: "updates.20240101.0000.bz2".to_string(),
+                    path: "https://archive.example.org/bgpdata/2024.01/updates.20240101.0000.bz2"
+                        .to_string(),
+                    size: Some(1_258_291),
+                    last_modified: Some("2024-01-01T00:15:00Z".to_string()),
+                    is_dir: false,
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_parse_apache_pre_and_nginx_index() {
+        let apache = r#"<pre><img src="/icons/blank.gif" alt="Icon "> <a href="?C=N;O=D">Name</a>                    <a href="?C=M;O=A">Last modified</a>      <a href="?C=S;O=A">Size</a>
+<hr><img src="/icons/back.gif" alt="[PARENTDIR]"> <a href="../">Parent Directory</a>                             -
+<a href="bview.20240101.0000.gz"><img src="/icons/compressed.gif" alt="[   ]"></a> <a href="bview.20240101.0000.gz">bview.20240101.0000.gz</a>  2024-01-01 00:40  388M
+</pre>"#;
+        let entries = parse_http_index(&base(), apache);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "bview.20240101.0000.gz");
+        assert_eq!(entries[0].size, Some(388 * 1024 * 1024));
+        assert_eq!(
+            entries[0].last_modified.as_deref(),
+            Some("2024-01-01T00:40:00Z")
+        );
+
+        let nginx = r#"<html><head><title>Index of /bgpdata/2024.01/</title></head>
+<body><h1>Index of /bgpdata/2024.01/</h1><hr><pre><a href="../">../</a>
+<a href="UPDATES/">UPDATES/</a>                                           05-Jan-2024 10:01                   -
+<a href="file%20name.txt">file name.txt</a>                                      31-Dec-2023 23:59               12345
+<a href="https://elsewhere.example.org/x.gz">x.gz</a>
+</pre><hr></body></html>"#;
+        let entries = parse_http_index(&base(), nginx);
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["UPDATES", "file name.txt"]);
+        assert!(entries[0].is_dir);
+        assert_eq!(
+            entries[0].last_modified.as_deref(),
+            Some("2024-01-05T10:01:00Z")
+        );
+        assert_eq!(entries[1].size, Some(12345));
+        assert_eq!(
+            entries[1].path,
+            "https://archive.example.org/bgpdata/2024.01/file%20name.txt"
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_parse_size_and_date_formats() {
+        assert_eq!(parse_size("-"), None);
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("4.0K"), Some(4096));
+        assert_eq!(parse_size("1.5GiB"), Some(1_610_612_736));
+        // Trailing tokens come from untrusted HTML and may end in any character.
+        assert_eq!(parse_size("café"), None);
+        assert_eq!(parse_size("…"), None);
+        assert_eq!(parse_date("2024-Jan-05").as_deref(), Some("2024-01-05"));
+        assert_eq!(parse_date("Parent"), None);
+        assert!(is_time("12:34:56"));
+        assert!(!is_time("1.2M"));
+    }
+}
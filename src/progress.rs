@@ -1,39 +1,395 @@
-//! Progress tracking reader for OneIO.
+//! Progress tracking readers and writers for OneIO.
+//!
+//! Readers report [`ProgressEvent`]s from the top of the reader stack, so
+//! each event carries both the raw bytes pulled from the source (counted by a
+//! [`RawCounter`] below the decoder) and the decompressed bytes returned to
+//! the caller. Writers report the same events from a [`ProgressWriter`] on
+//! top of the encoder, with the compressed bytes counted by a
+//! [`CountingWriter`] below it. Events are throttled according to
+//! [`ProgressOptions`].
 
-use std::io::Read;
+use crate::writer::FinishWrite;
+use crate::OneIoError;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-/// Progress reader wrapper that tracks bytes read
+/// Progress of a read, download or write, passed to progress callbacks.
+///
+/// For writers, the raw bytes are the compressed bytes handed to the
+/// destination and the decompressed bytes the uncompressed bytes written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct ProgressEvent {
+    /// Raw bytes read from the source so far, before decompression.
+    pub raw_bytes: u64,
+    /// Bytes returned to the caller so far, after decompression.
+    pub decompressed_bytes: u64,
+    /// Raw size of the source, if the backend reported one.
+    pub total_bytes: Option<u64>,
+    /// Time since the reader was created.
+    pub elapsed: Duration,
+    /// Number of times the transfer was resumed after a dropped connection.
+    pub resumes: u32,
+    /// Whether this is the final event, sent once when the reader hits EOF
+    /// or the writer is finished.
+    pub done: bool,
+}
+
+impl ProgressEvent {
+    /// Average raw throughput in bytes per second since the reader was created.
+    pub fn throughput(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.raw_bytes as f64 / secs,
+            _ => 0.0,
+        }
+    }
+
+    /// Fraction of the raw bytes read, between 0 and 1, if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total_bytes {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.raw_bytes as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+/// How often progress callbacks fire.
+///
+/// An event is sent on the first read or write, then whenever the time
+/// interval or the raw byte interval since the last event is reached,
+/// whichever comes first, and always once more at EOF or when a writer is
+/// finished. Without any interval, every read or write produces an event.
+/// The default is a 100 ms time interval.
+///
+/// ```rust
+/// use oneio::ProgressOptions;
+/// use std::time::Duration;
+///
+/// let options = ProgressOptions::new()
+///     .interval(Duration::from_secs(1))
+///     .bytes(16 * 1024 * 1024);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressOptions {
+    interval: Option<Duration>,
+    bytes: Option<u64>,
+}
+
+impl Default for ProgressOptions {
+    fn default() -> Self {
+        Self {
+            interval: Some(Duration::from_millis(100)),
+            bytes: None,
+        }
+    }
+}
+
+impl ProgressOptions {
+    /// Creates the default options: an event at most every 100 ms.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options that send an event on every read.
+    pub fn every_read() -> Self {
+        Self {
+            interval: None,
+            bytes: None,
+        }
+    }
+
+    /// Sends an event once `interval` has passed since the previous one.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Sends an event once `bytes` raw bytes were read since the previous one.
+    pub fn bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    fn is_due(&self, since_last: Duration, bytes_since_last: u64) -> bool {
+        if self.interval.is_none() && self.bytes.is_none() {
+            return true;
+        }
+        self.interval.is_some_and(|interval| since_last >= interval)
+            || self.bytes.is_some_and(|bytes| bytes_since_last >= bytes)
+    }
+}
+
+/// Counters shared between the layers of a progress-tracked reader.
+#[derive(Debug, Default)]
+pub(crate) struct ProgressCounters {
+    raw_bytes: AtomicU64,
+    resumes: AtomicU32,
+}
+
+impl ProgressCounters {
+    /// Records that the transfer was resumed.
+    #[cfg(feature = "http")]
+    pub(crate) fn resumed(&self) {
+        self.resumes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of resumes recorded so far.
+    pub(crate) fn resumes(&self) -> u32 {
+        self.resumes.load(Ordering::Relaxed)
+    }
+}
+
+/// Reader layer directly above the source that counts raw bytes.
+pub(crate) struct RawCounter<R> {
+    inner: R,
+    counters: Arc<ProgressCounters>,
+}
+
+impl<R: Read> RawCounter<R> {
+    pub(crate) fn new(inner: R, counters: Arc<ProgressCounters>) -> Self {
+        Self { inner, counters }
+    }
+}
+
+impl<R: Read> Read for RawCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.counters
+            .raw_bytes
+            .fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Progress reader wrapper at the top of a reader stack that sends
+/// throttled [`ProgressEvent`]s.
 pub(crate) struct ProgressReader<R, F> {
     inner: R,
-    bytes_read: u64,
-    total_size: u64,
+    counters: Arc<ProgressCounters>,
+    decompressed_bytes: u64,
+    total_bytes: Option<u64>,
+    options: ProgressOptions,
+    started: Instant,
+    /// Time and raw byte count of the last event.
+    last_event: Option<(Instant, u64)>,
+    done: bool,
     callback: F,
 }
 
 impl<R: Read, F> ProgressReader<R, F>
 where
-    F: Fn(u64, u64) + Send,
+    F: Fn(&ProgressEvent) + Send,
 {
-    pub(crate) fn new(inner: R, total_size: u64, callback: F) -> Self {
+    pub(crate) fn new(
+        inner: R,
+        counters: Arc<ProgressCounters>,
+        total_bytes: Option<u64>,
+        options: ProgressOptions,
+        callback: F,
+    ) -> Self {
         Self {
             inner,
-            bytes_read: 0,
-            total_size,
+            counters,
+            decompressed_bytes: 0,
+            total_bytes,
+            options,
+            started: Instant::now(),
+            last_event: None,
+            done: false,
             callback,
         }
     }
+
+    fn report(&mut self, now: Instant, raw_bytes: u64) {
+        self.last_event = Some((now, raw_bytes));
+        (self.callback)(&ProgressEvent {
+            raw_bytes,
+            decompressed_bytes: self.decompressed_bytes,
+            total_bytes: self.total_bytes,
+            elapsed: now - self.started,
+            resumes: self.counters.resumes(),
+            done: self.done,
+        });
+    }
 }
 
 impl<R: Read, F> Read for ProgressReader<R, F>
 where
-    F: Fn(u64, u64) + Send,
+    F: Fn(&ProgressEvent) + Send,
 {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let bytes_read = self.inner.read(buf)?;
-        if bytes_read > 0 {
-            self.bytes_read += bytes_read as u64;
-            (self.callback)(self.bytes_read, self.total_size);
+        let n = self.inner.read(buf)?;
+        let now = Instant::now();
+        let raw_bytes = self.counters.raw_bytes.load(Ordering::Relaxed);
+        if n > 0 {
+            self.decompressed_bytes += n as u64;
+            let due = match self.last_event {
+                None => true,
+                Some((at, bytes)) => self.options.is_due(now - at, raw_bytes - bytes),
+            };
+            if due {
+                self.report(now, raw_bytes);
+            }
+        } else if !buf.is_empty() && !self.done {
+            self.done = true;
+            self.report(now, raw_bytes);
+        }
+        Ok(n)
+    }
+}
+
+/// Writer layer below the compression encoder that counts the bytes handed
+/// to the destination.
+pub(crate) struct CountingWriter {
+    inner: Box<dyn FinishWrite>,
+    count: Arc<AtomicU64>,
+}
+
+impl CountingWriter {
+    pub(crate) fn new(inner: Box<dyn FinishWrite>, count: Arc<AtomicU64>) -> Self {
+        Self { inner, count }
+    }
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl FinishWrite for CountingWriter {
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+        self.inner.finish_write()
+    }
+
+    fn discard(self: Box<Self>) {
+        self.inner.discard()
+    }
+}
+
+/// Progress writer wrapper that sends throttled [`ProgressEvent`]s with the
+/// uncompressed bytes written and the compressed bytes counted by a
+/// [`CountingWriter`] further down the stack.
+pub(crate) struct ProgressWriter<F> {
+    inner: Box<dyn FinishWrite>,
+    bytes_written: u64,
+    compressed: Arc<AtomicU64>,
+    options: ProgressOptions,
+    started: Instant,
+    /// Time and compressed byte count of the last event.
+    last_event: Option<(Instant, u64)>,
+    callback: F,
+}
+
+impl<F> ProgressWriter<F>
+where
+    F: Fn(&ProgressEvent) + Send,
+{
+    pub(crate) fn new(
+        inner: Box<dyn FinishWrite>,
+        compressed: Arc<AtomicU64>,
+        options: ProgressOptions,
+        callback: F,
+    ) -> Self {
+        Self {
+            inner,
+            bytes_written: 0,
+            compressed,
+            options,
+            started: Instant::now(),
+            last_event: None,
+            callback,
+        }
+    }
+
+    /// Builds the event for `bytes_written` uncompressed and `raw_bytes`
+    /// compressed bytes.
+    fn event(
+        started: Instant,
+        now: Instant,
+        raw_bytes: u64,
+        bytes_written: u64,
+        done: bool,
+    ) -> ProgressEvent {
+        ProgressEvent {
+            raw_bytes,
+            decompressed_bytes: bytes_written,
+            total_bytes: None,
+            elapsed: now - started,
+            resumes: 0,
+            done,
+        }
+    }
+}
+
+impl<F> Write for ProgressWriter<F>
+where
+    F: Fn(&ProgressEvent) + Send,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        if n > 0 {
+            self.bytes_written += n as u64;
+            let now = Instant::now();
+            let raw_bytes = self.compressed.load(Ordering::Relaxed);
+            let due = match self.last_event {
+                None => true,
+                Some((at, bytes)) => self.options.is_due(now - at, raw_bytes - bytes),
+            };
+            if due {
+                self.last_event = Some((now, raw_bytes));
+                (self.callback)(&Self::event(
+                    self.started,
+                    now,
+                    raw_bytes,
+                    self.bytes_written,
+                    false,
+                ));
+            }
         }
-        Ok(bytes_read)
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<F> FinishWrite for ProgressWriter<F>
+where
+    F: Fn(&ProgressEvent) + Send,
+{
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+        let Self {
+            inner,
+            bytes_written,
+            compressed,
+            started,
+            callback,
+            ..
+        } = *self;
+        // The codec trailer is only written here, so report the final sizes.
+        inner.finish_write()?;
+        callback(&Self::event(
+            started,
+            Instant::now(),
+            compressed.load(Ordering::Relaxed),
+            bytes_written,
+            true,
+        ));
+        Ok(())
+    }
+
+    fn discard(self: Box<Self>) {
+        self.inner.discard()
     }
 }
@@ -0,0 +1,312 @@
+//! Token-bucket bandwidth limiting.
+//!
+//! A [`RateLimiter`] is configured on the builder with a global budget, a
+//! per-host budget or both, and is shared by every clone of the resulting
+//! [`OneIo`](crate::OneIo). Transfers to or from a remote path take bytes
+//! from the global bucket and from the bucket of the path's host (the URL
+//! authority; the bucket name for S3 paths). Buckets hold up to one second
+//! of budget and may go into debt, so a transfer that overdraws a bucket
+//! sleeps until the debt is repaid, and concurrent transfers share one
+//! budget fairly. Local paths are never limited.
+
+use crate::writer::FinishWrite;
+use crate::OneIoError;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Bandwidth budgets shared by all clones of a [`OneIo`](crate::OneIo).
+#[derive(Clone, Default)]
+pub(crate) struct RateLimiter {
+    global: Option<Arc<TokenBucket>>,
+    per_host: Option<PerHost>,
+}
+
+#[derive(Clone)]
+struct PerHost {
+    bytes_per_second: u64,
+    /// Buckets of the hosts transferred so far. Idle buckets that have
+    /// refilled are pruned when a new host is added, as they behave exactly
+    /// like a new bucket.
+    buckets: Arc<Mutex<HashMap<String, Arc<TokenBucket>>>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(global: Option<u64>, per_host: Option<u64>) -> Self {
+        Self {
+            global: global.map(|rate| Arc::new(TokenBucket::new(rate))),
+            per_host: per_host.map(|bytes_per_second| PerHost {
+                bytes_per_second,
+                buckets: Arc::default(),
+            }),
+        }
+    }
+
+    /// Returns the throttle for transfers of `path`, or `None` if the path is
+    /// local or no limit is configured.
+    pub(crate) fn for_path(&self, path: &str) -> Option<Throttle> {
+        match crate::get_protocol(path) {
+            None | Some("file") => return None,
+            Some(_) => {}
+        }
+        let mut buckets: Vec<Arc<TokenBucket>> = self.global.iter().cloned().collect();
+        if let Some(per_host) = &self.per_host {
+            let mut hosts = per_host.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let host = host_key(path);
+            let bucket = match hosts.get(&host) {
+                Some(bucket) => bucket.clone(),
+                None => {
+                    hosts.retain(|_, bucket| Arc::strong_count(bucket) > 1 || !bucket.is_full());
+                    let bucket = Arc::new(TokenBucket::new(per_host.bytes_per_second));
+                    hosts.insert(host, bucket.clone());
+                    bucket
+                }
+            };
+            buckets.push(bucket);
+        }
+        if buckets.is_empty() {
+            None
+        } else {
+            Some(Throttle { buckets })
+        }
+    }
+
+    /// Wraps `reader` in the throttle for `path`, if any.
+    pub(crate) fn reader(&self, path: &str, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        match self.for_path(path) {
+            Some(throttle) => Box::new(throttle.reader(reader)),
+            None => reader,
+        }
+    }
+}
+
+/// Lowercase URL authority of `path` without user info.
+fn host_key(path: &str) -> String {
+    let rest = path.split_once("://").map_or(path, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    host.to_ascii_lowercase()
+}
+
+/// A token bucket refilled at a fixed rate, holding at most one second of
+/// budget.
+struct TokenBucket {
+    bytes_per_second: u64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    /// Available bytes; negative when transfers have overdrawn the bucket.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            state: Mutex::new(BucketState {
+                tokens: bytes_per_second as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Whether the bucket has refilled to its full second of budget since it
+    /// was last taken from.
+    fn is_full(&self) -> bool {
+        let rate = self.bytes_per_second as f64;
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tokens + state.refilled_at.elapsed().as_secs_f64() * rate >= rate
+    }
+
+    /// Takes `bytes` from the bucket and returns how long the caller has to
+    /// wait for the bucket to be back out of debt.
+    fn take(&self, bytes: u64) -> Duration {
+        let rate = self.bytes_per_second as f64;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = (now - state.refilled_at).as_secs_f64() * rate;
+        state.tokens = (state.tokens + refill).min(rate) - bytes as f64;
+        state.refilled_at = now;
+        match state.tokens {
+            tokens if tokens < 0.0 => Duration::from_secs_f64(-tokens / rate),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// The buckets a single transfer draws from.
+#[derive(Clone)]
+pub(crate) struct Throttle {
+    buckets: Vec<Arc<TokenBucket>>,
+}
+
+impl Throttle {
+    /// Accounts for `bytes` transferred, sleeping while any bucket is in debt.
+    pub(crate) fn consume(&self, bytes: u64) {
+        let wait = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.take(bytes))
+            .max()
+            .unwrap_or_default();
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Largest transfer accounted at once, about a tenth of a second at the
+    /// slowest rate, so throttled streams stay smooth.
+    fn chunk_size(&self) -> usize {
+        let rate = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.bytes_per_second)
+            .min()
+            .unwrap_or(u64::MAX);
+        (rate / 10).clamp(1, 64 * 1024) as usize
+    }
+
+    pub(crate) fn reader<R: Read>(self, inner: R) -> ThrottledReader<R> {
+        ThrottledReader {
+            chunk_size: self.chunk_size(),
+            inner,
+            throttle: self,
+        }
+    }
+
+    pub(crate) fn writer(self, inner: Box<dyn FinishWrite>) -> ThrottledWriter {
+        ThrottledWriter {
+            chunk_size: self.chunk_size(),
+            inner,
+            throttle: self,
+        }
+    }
+}
+
+/// Reader wrapper that limits the rate at which bytes are read.
+pub(crate) struct ThrottledReader<R> {
+    inner: R,
+    throttle: Throttle,
+    chunk_size: usize,
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.chunk_size);
+        let n = self.inner.read(&mut buf[..len])?;
+        self.throttle.consume(n as u64);
+        Ok(n)
+    }
+}
+
+/// Writer layer that limits the rate at which bytes reach the destination.
+pub(crate) struct ThrottledWriter {
+    inner: Box<dyn FinishWrite>,
+    throttle: Throttle,
+    chunk_size: usize,
+}
+
+impl Write for ThrottledWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.chunk_size);
+        let n = self.inner.write(&buf[..len])?;
+        self.throttle.consume(n as u64);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl FinishWrite for ThrottledWriter {
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+        self.inner.finish_write()
+    }
+
+    fn discard(self: Box<Self>) {
+        self.inner.discard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_key() {
+        assert_eq!(
+            host_key("https://User@Example.com:8443/a/b?c"),
+            "example.com:8443"
+        );
+        assert_eq!(host_key("s3://bucket/key"), "bucket");
+        assert_eq!(host_key("http://host?x=1"), "host");
+    }
+
+    #[test]
+    fn test_token_bucket_debt() {
+        let bucket = TokenBucket::new(1000);
+        // The first second of budget is available immediately.
+        assert_eq!(bucket.take(1000), Duration::ZERO);
+        let wait = bucket.take(500);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_idle_host_buckets_are_pruned_once_full() {
+        let limiter = RateLimiter::new(None, Some(1000));
+        let hosts = || {
+            let mut hosts: Vec<String> = limiter
+                .per_host
+                .as_ref()
+                .unwrap()
+                .buckets
+                .lock()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect();
+            hosts.sort();
+            hosts
+        };
+        let a = limiter.for_path("https://a.example/x").unwrap();
+        let again = limiter.for_path("https://a.example/y").unwrap();
+        assert!(Arc::ptr_eq(&a.buckets[0], &again.buckets[0]));
+        a.buckets[0].take(1500);
+        drop((a, again));
+
+        // In debt, and then refilling: the idle bucket is kept.
+        let _b = limiter.for_path("https://b.example/x").unwrap();
+        assert_eq!(hosts(), ["a.example", "b.example"]);
+        let a = limiter.for_path("https://a.example/x").unwrap();
+        {
+            let mut state = a.buckets[0].state.lock().unwrap();
+            state.tokens = 0.0;
+            state.refilled_at = Instant::now() - Duration::from_millis(500);
+        }
+        drop(a);
+        let _c = limiter.for_path("https://c.example/x").unwrap();
+        assert_eq!(hosts(), ["a.example", "b.example", "c.example"]);
+
+        // Back to full: pruned, while buckets still in use are kept.
+        let a = limiter.for_path("https://a.example/x").unwrap();
+        a.buckets[0].state.lock().unwrap().refilled_at = Instant::now() - Duration::from_secs(2);
+        drop(a);
+        let _d = limiter.for_path("https://d.example/x").unwrap();
+        assert_eq!(hosts(), ["b.example", "c.example", "d.example"]);
+    }
+
+    #[test]
+    fn test_local_paths_are_not_limited() {
+        let limiter = RateLimiter::new(Some(1), Some(1));
+        assert!(limiter.for_path("tests/test_data.txt").is_none());
+        assert!(limiter.for_path("file:///tmp/x").is_none());
+        assert!(limiter.for_path("https://host/x").is_some());
+        assert!(RateLimiter::default().for_path("https://host/x").is_none());
+    }
+}
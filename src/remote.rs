@@ -1,5 +1,6 @@
 //! This module provides functionality to handle remote file operations such as downloading files
 //! from HTTP, FTP, and S3 protocols.
+use crate::backend::{ListEntry, ObjectStat};
 use crate::client::OneIo;
 use crate::OneIoError;
 #[cfg(feature = "http")]
@@ -9,6 +10,92 @@ use std::io::Read;
 
 #[cfg(feature = "ftp")]
 pub(crate) fn get_ftp_reader_raw(path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+    let (mut ftp_stream, remote_path) = connect_ftp(path)?;
+    let reader = Box::new(ftp_stream.retr_as_stream(remote_path)?);
+    Ok(reader)
+}
+
+/// Opens an FTP reader starting at `offset`, using `REST` to skip ahead.
+///
+/// Reads at most `len` bytes, or to the end of the file when `len` is `None`.
+/// Servers that reject `REST` produce [`OneIoError::RangeNotSupported`].
+#[cfg(feature = "ftp")]
+pub(crate) fn get_ftp_range_reader(
+    path: &str,
+    offset: u64,
+    len: Option<u64>,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    if len == Some(0) {
+        return Ok(Box::new(std::io::empty()));
+    }
+
+    let (mut ftp_stream, remote_path) = connect_ftp(path)?;
+    if offset > 0 {
+        let offset =
+            usize::try_from(offset).map_err(|_| OneIoError::RangeNotSupported(path.to_string()))?;
+        ftp_stream
+            .resume_transfer(offset)
+            .map_err(|_| OneIoError::RangeNotSupported(path.to_string()))?;
+    }
+    let stream = ftp_stream.retr_as_stream(remote_path)?;
+    Ok(match len {
+        Some(len) => Box::new(stream.take(len)),
+        None => Box::new(stream),
+    })
+}
+
+/// Lists an FTP directory with `MLSD`, falling back to `LIST` for servers
+/// without machine-readable listings.
+#[cfg(feature = "ftp")]
+pub(crate) fn list_ftp_dir(path: &str) -> Result<Vec<ListEntry>, OneIoError> {
+    use suppaftp::list::{File, ListParser};
+
+    let (mut ftp_stream, remote_path) = connect_ftp(path)?;
+    let dir = match remote_path.trim_end_matches('/') {
+        "" => "/".to_string(),
+        dir => format!("/{dir}"),
+    };
+    let files: Vec<File> = match ftp_stream.mlsd(Some(&dir)) {
+        Ok(lines) => lines
+            .iter()
+            .filter_map(|line| ListParser::parse_mlsd(line).ok())
+            .collect(),
+        Err(_) => ftp_stream
+            .list(Some(&dir))?
+            .iter()
+            .filter_map(|line| {
+                ListParser::parse_posix(line)
+                    .or_else(|_| ListParser::parse_dos(line))
+                    .ok()
+            })
+            .collect(),
+    };
+    let _ = ftp_stream.quit();
+
+    let base = path.trim_end_matches('/');
+    Ok(files
+        .into_iter()
+        .filter(|file| !matches!(file.name(), "." | ".."))
+        .map(|file| {
+            let is_dir = file.is_directory();
+            ListEntry {
+                name: file.name().to_string(),
+                path: if is_dir {
+                    format!("{base}/{}/", file.name())
+                } else {
+                    format!("{base}/{}", file.name())
+                },
+                size: (!is_dir).then_some(file.size() as u64),
+                last_modified: crate::listing::format_system_time(file.modified()),
+                is_dir,
+            }
+        })
+        .collect())
+}
+
+/// Connects and logs in anonymously, returning the stream and the remote path.
+#[cfg(feature = "ftp")]
+fn connect_ftp(path: &str) -> Result<(suppaftp::FtpStream, &str), OneIoError> {
     if !path.starts_with("ftp://") {
         return Err(OneIoError::NotSupported(path.to_string()));
     }
@@ -31,8 +118,7 @@ pub(crate) fn get_ftp_reader_raw(path: &str) -> Result<Box<dyn Read + Send>, One
     // use anonymous login
     ftp_stream.login("anonymous", "oneio")?;
     ftp_stream.transfer_type(suppaftp::types::FileType::Binary)?;
-    let reader = Box::new(ftp_stream.retr_as_stream(remote_path)?);
-    Ok(reader)
+    Ok((ftp_stream, remote_path))
 }
 
 #[cfg(feature = "http")]
@@ -76,6 +162,45 @@ fn get_http_reader_raw_inner(
     Ok(res)
 }
 
+/// Opens a ranged GET over `len` bytes (or the rest) of `path` from `offset`.
+///
+/// Requests pin `Accept-Encoding: identity` so offsets refer to the stored
+/// bytes. See [`crate::seekable::range_response_reader`] for how the
+/// response status is interpreted.
+#[cfg(feature = "http")]
+pub(crate) fn get_http_range_reader(
+    path: &str,
+    client: &Client,
+    offset: u64,
+    len: Option<u64>,
+) -> Result<Box<dyn std::io::Read + Send>, OneIoError> {
+    if len == Some(0) {
+        return Ok(Box::new(std::io::empty()));
+    }
+
+    let response = client
+        .get(path)
+        .header(
+            reqwest::header::RANGE,
+            crate::seekable::range_header(offset, len),
+        )
+        .header(reqwest::header::ACCEPT_ENCODING, "identity")
+        .send()?;
+    crate::seekable::range_response_reader(response, path, offset, len, |response| {
+        match response.error_for_status() {
+            Ok(response) => OneIoError::Status {
+                service: "HTTP",
+                code: response.status().as_u16(),
+                message: None,
+            },
+            Err(e) => OneIoError::NetworkWithContext {
+                source: Box::new(e),
+                url: path.to_string(),
+            },
+        }
+    })
+}
+
 /// Creates a reqwest blocking client with custom headers.
 ///
 /// Prefer [`OneIo::builder()`] for reusable configuration. This helper is
@@ -99,20 +224,85 @@ where
     Ok(builder.build()?.http_client().clone())
 }
 
+/// Lists an HTTP(S) directory by parsing its HTML autoindex page.
+///
+/// Entry URLs are resolved against the final URL after redirects, treated as
+/// a directory. Responses that are not HTML produce
+/// [`OneIoError::NotSupported`].
 #[cfg(feature = "http")]
-pub(crate) fn get_http_content_length(path: &str, client: &Client) -> Result<u64, OneIoError> {
-    let response = client.head(path).send()?.error_for_status()?;
-
-    response
+pub(crate) fn list_http_dir(path: &str, client: &Client) -> Result<Vec<ListEntry>, OneIoError> {
+    let response = get_http_reader_raw(path, client)?;
+    let content_type = response
         .headers()
-        .get("content-length")
+        .get(reqwest::header::CONTENT_TYPE)
         .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse().ok())
-        .ok_or_else(|| {
-            OneIoError::NotSupported(
-                "Cannot determine file size - server doesn't provide Content-Length".to_string(),
-            )
-        })
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+    if !content_type.contains("html") {
+        return Err(OneIoError::NotSupported(format!(
+            "list: {path} is not an HTML index ({content_type})"
+        )));
+    }
+
+    let mut base = response.url().clone();
+    if !base.path().ends_with('/') {
+        base.set_path(&format!("{}/", base.path()));
+    }
+    let html = response.text()?;
+    Ok(crate::listing::parse_http_index(&base, &html))
+}
+
+#[cfg(feature = "http")]
+pub(crate) fn get_http_stat(path: &str, client: &Client) -> Result<ObjectStat, OneIoError> {
+    let response = client.head(path).send()?.error_for_status()?;
+    Ok(stat_from_headers(response.headers()))
+}
+
+/// Sends a conditional GET with `If-None-Match` / `If-Modified-Since` built
+/// from `cached`. Returns `None` on `304 Not Modified`.
+#[cfg(feature = "http")]
+pub(crate) fn get_http_reader_if_changed(
+    path: &str,
+    client: &Client,
+    cached: &ObjectStat,
+) -> Result<Option<crate::backend::ChangedObject>, OneIoError> {
+    let mut request = client.get(path);
+    if let Some(etag) = &cached.etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &cached.last_modified {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request.send()?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    let response = response
+        .error_for_status()
+        .map_err(|e| OneIoError::NetworkWithContext {
+            source: Box::new(e),
+            url: path.to_string(),
+        })?;
+    Ok(Some((
+        stat_from_headers(response.headers()),
+        Box::new(response),
+    )))
+}
+
+#[cfg(feature = "http")]
+fn stat_from_headers(headers: &reqwest::header::HeaderMap) -> ObjectStat {
+    let header = |name: reqwest::header::HeaderName| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+
+    ObjectStat {
+        content_length: header(reqwest::header::CONTENT_LENGTH).and_then(|s| s.parse().ok()),
+        last_modified: header(reqwest::header::LAST_MODIFIED),
+        etag: header(reqwest::header::ETAG),
+    }
 }
 
 /// Check if a remote or local file exists.
@@ -6,11 +6,13 @@
 //! the server may close the idle connection. This reader detects the failure
 //! and reconnects from where it left off.
 
+use crate::progress::ProgressCounters;
 use reqwest::{
     blocking::{Client, Response},
     header::HeaderValue,
 };
 use std::io::{self, Read};
+use std::sync::Arc;
 
 /// Maximum number of consecutive retry attempts before giving up.
 const MAX_RETRIES: u32 = 5;
@@ -28,7 +30,7 @@ const BASE_RETRY_DELAY_MS: u64 = 1;
 /// The header has the form `bytes <start>-<end>/<total>` (RFC 9110 §14.4);
 /// only `<start>` is needed to confirm where the server resumed. Returns
 /// `None` if the value is not in the expected form.
-fn parse_content_range_start(value: &str) -> Option<u64> {
+pub(crate) fn parse_content_range_start(value: &str) -> Option<u64> {
     // "bytes 5-9/10" -> "5-9/10" -> "5"
     let mut parts = value.split_whitespace();
     let unit = parts.next()?;
@@ -40,6 +42,19 @@ fn parse_content_range_start(value: &str) -> Option<u64> {
     start.trim().parse().ok()
 }
 
+/// Parses the complete length from a `Content-Range` header value.
+///
+/// Accepts both `bytes <start>-<end>/<total>` and the unsatisfied-range form
+/// `bytes */<total>` sent with a `416`. Returns `None` if the total is unknown
+/// (`*`) or the value is not in the expected form.
+pub(crate) fn parse_content_range_total(value: &str) -> Option<u64> {
+    let (unit, range) = value.trim().split_once(char::is_whitespace)?;
+    if !unit.eq_ignore_ascii_case("bytes") {
+        return None;
+    }
+    range.rsplit_once('/')?.1.trim().parse().ok()
+}
+
 /// How to interpret a `416 Range Not Satisfiable` reply to a resume request.
 enum RangeNotSatisfiable {
     /// The resume offset is at or past the known content length, so the body
@@ -137,6 +152,8 @@ pub(crate) struct ResumableHttpReader {
     last_modified: Option<HeaderValue>,
     /// The original response's `ETag` header value.
     etag: Option<HeaderValue>,
+    /// Progress counters notified of each successful resume.
+    progress: Option<Arc<ProgressCounters>>,
 }
 
 /// Outcome of an attempt to resume the download from the current offset.
@@ -174,9 +191,16 @@ impl ResumableHttpReader {
             content_length,
             last_modified,
             etag,
+            progress: None,
         }
     }
 
+    /// Counts each successful resume in `counters`.
+    pub(crate) fn with_progress(mut self, counters: Arc<ProgressCounters>) -> Self {
+        self.progress = Some(counters);
+        self
+    }
+
     /// Reconnects and resumes the download from `self.offset`.
     ///
     /// The request itself is retried up to `MAX_RETRIES` times with exponential
@@ -333,7 +357,12 @@ impl Read for ResumableHttpReader {
 
                     match self.resume()? {
                         // Read again from the freshly reconnected response.
-                        Resume::Resumed => continue,
+                        Resume::Resumed => {
+                            if let Some(progress) = &self.progress {
+                                progress.resumed();
+                            }
+                            continue;
+                        }
                         // Nothing more to read.
                         Resume::Eof => return Ok(0),
                         // Can't resume — surface the original failure.
@@ -438,6 +467,43 @@ mod tests {
         handle.join().unwrap();
     }
 
+    // Check each successful resume is counted for progress events
+    #[test]
+    fn drop_resume_counts_resumes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let url = format!("http://127.0.0.1:{}/data.txt", port);
+
+        let handle = thread::spawn(move || {
+            let (mut stream1, _) = listener.accept().unwrap();
+            read_request(&mut stream1);
+
+            let response_part1 =
+                "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nETag: \"v1\"\r\n\r\n12345";
+            stream1.write_all(response_part1.as_bytes()).unwrap();
+            drop(stream1);
+
+            let (mut stream2, _) = listener.accept().unwrap();
+            read_request(&mut stream2);
+
+            let response_part2 = "HTTP/1.1 206 Partial Content\r\nContent-Length: 5\r\nContent-Range: bytes 5-9/10\r\nETag: \"v1\"\r\n\r\n67890";
+            stream2.write_all(response_part2.as_bytes()).unwrap();
+        });
+
+        let client = reqwest::blocking::Client::new();
+        let resp = client.get(&url).send().unwrap();
+        let counters = Arc::new(crate::progress::ProgressCounters::default());
+        let mut reader =
+            ResumableHttpReader::new(client, url, resp).with_progress(counters.clone());
+
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf).unwrap();
+
+        assert_eq!(buf.as_str(), "1234567890");
+        assert_eq!(counters.resumes(), 1);
+        handle.join().unwrap();
+    }
+
     // Check reader resumes from a 200 only if offset is zero
     // A server might reply with a 200 to a range request asking for the whole content
     #[test]
@@ -575,6 +641,16 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_content_range_total_forms() {
+        use crate::resumable_http::parse_content_range_total;
+
+        assert_eq!(parse_content_range_total("bytes 5-9/10"), Some(10));
+        assert_eq!(parse_content_range_total("bytes */42"), Some(42));
+        assert_eq!(parse_content_range_total("bytes 0-9/*"), None);
+        assert_eq!(parse_content_range_total("items 0-9/10"), None);
+    }
+
     // Exhaustively check the validator comparison logic, which drives whether a
     // resumed response is accepted, rejected as modified, or rejected as
     // unverifiable.
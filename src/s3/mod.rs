@@ -289,6 +289,38 @@ pub fn s3_reader(bucket: &str, key: &str) -> Result<Box<dyn Read + Send>, OneIoE
     Ok(Box::new(response))
 }
 
+/// Opens a ranged reader over `len` bytes (or the rest) of an S3 object from `offset`.
+pub(crate) fn s3_range_reader(
+    bucket: &str,
+    key: &str,
+    offset: u64,
+    len: Option<u64>,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    if len == Some(0) {
+        return Ok(Box::new(std::io::empty()));
+    }
+
+    let config = config::S3Config::from_env(bucket)?;
+    let rusty_bucket = config.rusty_bucket()?;
+    let creds = config.rusty_credentials();
+    let action = rusty_bucket.get_object(Some(&creds), key);
+    let url = repair_leading_slash_action_url(action.sign(config.ttl), &config, key, "GET")?;
+    let response = get_s3_client()
+        .get(url)
+        .header(
+            reqwest::header::RANGE,
+            crate::seekable::range_header(offset, len),
+        )
+        .send()?;
+    crate::seekable::range_response_reader(
+        response,
+        &format!("s3://{bucket}/{key}"),
+        offset,
+        len,
+        s3_error_from_response,
+    )
+}
+
 /// Downloads a file from an S3 bucket and saves it locally.
 pub fn s3_download(bucket: &str, key: &str, file_path: &str) -> Result<(), OneIoError> {
     let mut reader = s3_reader(bucket, key)?;
@@ -299,6 +331,37 @@ pub fn s3_download(bucket: &str, key: &str, file_path: &str) -> Result<(), OneIo
 
 /// Uploads a file to an S3 bucket at the specified path.
 pub fn s3_upload(bucket: &str, key: &str, file_path: &str) -> Result<(), OneIoError> {
+    s3_upload_with_progress(bucket, key, file_path, |_, _, _| {})
+}
+
+/// Uploads a file to an S3 bucket, reporting progress.
+///
+/// `progress` receives the bytes uploaded so far, the file size and the
+/// number of parts completed. It is called after each multipart part is
+/// acknowledged; files below the multipart threshold are sent with a single
+/// PUT and reported once as one part. Calls are not throttled with
+/// [`ProgressOptions`](crate::ProgressOptions): parts are at least 5 MiB, so
+/// there is at most one call per part.
+pub fn s3_upload_with_progress<F>(
+    bucket: &str,
+    key: &str,
+    file_path: &str,
+    progress: F,
+) -> Result<(), OneIoError>
+where
+    F: Fn(u64, u64, usize) + Send,
+{
+    upload_file(bucket, key, file_path, &progress, None)
+}
+
+/// Uploads a file, limiting its bandwidth with `throttle` if given.
+pub(crate) fn upload_file(
+    bucket: &str,
+    key: &str,
+    file_path: &str,
+    progress: &dyn Fn(u64, u64, usize),
+    throttle: Option<crate::rate_limit::Throttle>,
+) -> Result<(), OneIoError> {
     // Early validation: check if file exists before attempting S3 operations
     if !std::path::Path::new(file_path).exists() {
         return Err(OneIoError::Io(std::io::Error::new(
@@ -313,24 +376,37 @@ pub fn s3_upload(bucket: &str, key: &str, file_path: &str) -> Result<(), OneIoEr
     let config = config::S3Config::from_env(bucket)?;
 
     if size < config.multipart_threshold {
-        upload_single(&config, key, file_path)
+        let file = std::fs::File::open(file_path)?;
+        match throttle {
+            Some(throttle) => upload_single(
+                &config,
+                key,
+                reqwest::blocking::Body::sized(throttle.reader(file), size),
+            )?,
+            None => upload_single(&config, key, file)?,
+        }
+        progress(size, size, 1);
+        Ok(())
     } else {
-        upload_multipart(&config, key, file_path, size)
+        upload_multipart(&config, key, file_path, size, progress, throttle.as_ref())
     }
 }
 
-fn upload_single(config: &config::S3Config, key: &str, file_path: &str) -> Result<(), OneIoError> {
+fn upload_single<B: Into<reqwest::blocking::Body>>(
+    config: &config::S3Config,
+    key: &str,
+    body: B,
+) -> Result<(), OneIoError> {
     let bucket = config.rusty_bucket()?;
     let creds = config.rusty_credentials();
 
-    let file = std::fs::File::open(file_path)?;
     let action = bucket.put_object(Some(&creds), key);
     let url = repair_leading_slash_action_url(action.sign(config.ttl), config, key, "PUT")?;
     ensure_s3_success(
         get_s3_client()
             .put(url)
             .timeout(S3_UPLOAD_REQUEST_TIMEOUT)
-            .body(file)
+            .body(body)
             .send()?,
     )?;
     Ok(())
@@ -412,40 +488,29 @@ fn is_retryable_error(err: &reqwest::Error) -> bool {
 /// Upload a single multipart part with retry, avoiding unnecessary clones.
 ///
 /// On the first attempt, `body` is moved into the request with zero copy.
-/// On retry (transient transport error), the part bytes are re-read from
-/// `file` at `offset` to reconstruct the request body. This avoids cloning
+/// On retry (transient transport error), `reread` is called to reconstruct
+/// the request body, e.g. by re-reading the part bytes from the source file
+/// or cloning a shared [`Bytes`](bytes::Bytes) handle. This avoids cloning
 /// the full chunk on every attempt — the happy path has no extra allocation.
-fn upload_part_with_retry(
+fn upload_part_with_retry<B, F>(
     url: &reqwest::Url,
-    body: Vec<u8>,
-    file: &mut std::fs::File,
-    offset: u64,
-    part_len: u64,
-) -> Result<Response, OneIoError> {
+    body: B,
+    mut reread: F,
+) -> Result<Response, OneIoError>
+where
+    B: Into<reqwest::blocking::Body>,
+    F: FnMut() -> Result<B, OneIoError>,
+{
     let (max_retries, mut backoff_ms) = s3_retry_config();
 
     // First attempt: move the body, no clone.
-    // Retry attempts: re-read from file at the recorded offset.
+    // Retry attempts: rebuild the body from the caller's source.
     let mut body = Some(body);
 
     for _attempt in 0..=max_retries {
         let request_body = match body.take() {
             Some(b) => b,
-            None => {
-                let mut buf = Vec::with_capacity(part_len as usize);
-                file.seek(SeekFrom::Start(offset))?;
-                file.by_ref().take(part_len).read_to_end(&mut buf)?;
-                if buf.len() as u64 != part_len {
-                    return Err(OneIoError::Io(std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        format!(
-                            "S3 retry part read was short: expected {part_len} bytes, got {}",
-                            buf.len()
-                        ),
-                    )));
-                }
-                buf
-            }
+            None => reread()?,
         };
 
         match get_s3_client()
@@ -465,46 +530,193 @@ fn upload_part_with_retry(
     unreachable!()
 }
 
+/// Re-reads `part_len` bytes at `offset` from `file` to rebuild a part body
+/// for a retried upload.
+fn reread_part(
+    file: &mut std::fs::File,
+    offset: u64,
+    part_len: u64,
+) -> Result<Vec<u8>, OneIoError> {
+    let mut buf = Vec::with_capacity(part_len as usize);
+    file.seek(SeekFrom::Start(offset))?;
+    file.by_ref().take(part_len).read_to_end(&mut buf)?;
+    if buf.len() as u64 != part_len {
+        return Err(OneIoError::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!(
+                "S3 retry part read was short: expected {part_len} bytes, got {}",
+                buf.len()
+            ),
+        )));
+    }
+    Ok(buf)
+}
+
+/// An in-progress multipart upload.
+///
+/// Created with [`MultipartUpload::create`], fed with [`upload_part`], and
+/// closed with either [`complete`] or [`abort`]. Every failure path in
+/// `upload_part` and `complete` leaves the upload to the caller to abort;
+/// `complete` aborts on its own when S3 rejects the final request.
+///
+/// [`upload_part`]: MultipartUpload::upload_part
+/// [`complete`]: MultipartUpload::complete
+/// [`abort`]: MultipartUpload::abort
+struct MultipartUpload {
+    config: config::S3Config,
+    bucket: rusty_s3::Bucket,
+    creds: rusty_s3::Credentials,
+    key: String,
+    upload_id: String,
+    etags: Vec<String>,
+}
+
+impl MultipartUpload {
+    /// Initiates a multipart upload for `key`.
+    fn create(config: &config::S3Config, key: &str) -> Result<Self, OneIoError> {
+        let bucket = config.rusty_bucket()?;
+        let creds = config.rusty_credentials();
+
+        let action = bucket.create_multipart_upload(Some(&creds), key);
+        let url = repair_leading_slash_action_url(action.sign(config.ttl), config, key, "POST")?;
+        let response = ensure_s3_success(send_with_retry(|| {
+            get_s3_client()
+                .post(url.clone())
+                .timeout(S3_UPLOAD_REQUEST_TIMEOUT)
+                .send()
+        })?)?;
+        let init_response =
+            rusty_s3::actions::CreateMultipartUpload::parse_response(response.text()?.as_bytes())
+                .map_err(|e| OneIoError::Network(Box::new(e)))?;
+
+        Ok(Self {
+            config: config.clone(),
+            upload_id: init_response.upload_id().to_string(),
+            bucket,
+            creds,
+            key: key.to_string(),
+            etags: Vec::new(),
+        })
+    }
+
+    /// Number of parts uploaded so far.
+    fn parts_uploaded(&self) -> usize {
+        self.etags.len()
+    }
+
+    /// Uploads the next part. `reread` rebuilds the body if a retry is needed.
+    fn upload_part<B, F>(&mut self, body: B, reread: F) -> Result<(), OneIoError>
+    where
+        B: Into<reqwest::blocking::Body>,
+        F: FnMut() -> Result<B, OneIoError>,
+    {
+        let part_number = u16::try_from(self.etags.len() + 1).map_err(|_| {
+            OneIoError::NotSupported("S3 multipart upload exceeds 10,000 parts".to_string())
+        })?;
+        let action =
+            self.bucket
+                .upload_part(Some(&self.creds), &self.key, part_number, &self.upload_id);
+        let url = repair_leading_slash_action_url(
+            action.sign(self.config.ttl),
+            &self.config,
+            &self.key,
+            "PUT",
+        )?;
+
+        let response = ensure_s3_success(upload_part_with_retry(&url, body, reread)?)?;
+        let etag = extract_etag(response.headers()).ok_or_else(|| {
+            OneIoError::NotSupported("Missing ETag in UploadPart response".into())
+        })?;
+        self.etags.push(etag);
+        Ok(())
+    }
+
+    /// Completes the upload, aborting it if S3 rejects the request.
+    fn complete(self) -> Result<(), OneIoError> {
+        let action = self.bucket.complete_multipart_upload(
+            Some(&self.creds),
+            &self.key,
+            &self.upload_id,
+            self.etags.iter().map(|s| s.as_str()),
+        );
+        let url = repair_leading_slash_action_url(
+            action.sign(self.config.ttl),
+            &self.config,
+            &self.key,
+            "POST",
+        )?;
+        let body = action.body();
+        let response = match send_with_retry(|| {
+            get_s3_client()
+                .post(url.clone())
+                .timeout(S3_UPLOAD_REQUEST_TIMEOUT)
+                .header("content-type", "application/xml")
+                .body(body.clone())
+                .send()
+        }) {
+            Ok(response) => response,
+            Err(e) => {
+                self.abort();
+                return Err(e);
+            }
+        };
+
+        // CompleteMultipartUpload can return 200 OK with an embedded <Error> body.
+        // Validate HTTP status first, then parse the body to confirm success.
+        if !response.status().is_success() {
+            self.abort();
+            return Err(s3_error_from_response(response));
+        }
+        let complete_body = response.text().unwrap_or_default();
+        if let Some(parsed) = parse_s3_error_xml(&complete_body) {
+            self.abort();
+            return Err(map_parsed_s3_error(200, parsed));
+        }
+
+        Ok(())
+    }
+
+    /// Aborts the upload so S3 discards the uploaded parts. Best effort.
+    fn abort(&self) {
+        let action =
+            self.bucket
+                .abort_multipart_upload(Some(&self.creds), &self.key, &self.upload_id);
+        if let Ok(url) = repair_leading_slash_action_url(
+            action.sign(self.config.ttl),
+            &self.config,
+            &self.key,
+            "DELETE",
+        ) {
+            let _ = get_s3_client().delete(url).send();
+        }
+    }
+}
+
 fn upload_multipart(
     config: &config::S3Config,
     key: &str,
     file_path: &str,
     size: u64,
+    progress: &dyn Fn(u64, u64, usize),
+    throttle: Option<&crate::rate_limit::Throttle>,
 ) -> Result<(), OneIoError> {
     let (chunk_size, total_parts) = calculate_chunk_size(size, config.multipart_chunk_size);
 
-    let bucket = config.rusty_bucket()?;
-    let creds = config.rusty_credentials();
-
     // 1. Initiate multipart upload
-    let action = bucket.create_multipart_upload(Some(&creds), key);
-    let url = repair_leading_slash_action_url(action.sign(config.ttl), config, key, "POST")?;
-    let response = ensure_s3_success(send_with_retry(|| {
-        get_s3_client()
-            .post(url.clone())
-            .timeout(S3_UPLOAD_REQUEST_TIMEOUT)
-            .send()
-    })?)?;
-    let init_response =
-        rusty_s3::actions::CreateMultipartUpload::parse_response(response.text()?.as_bytes())
-            .map_err(|e| OneIoError::Network(Box::new(e)))?;
-    let upload_id = init_response.upload_id().to_string();
+    let mut upload = MultipartUpload::create(config, key)?;
 
     // 2. Upload parts with abort-on-failure guard
-    let mut parts: Vec<String> = Vec::with_capacity(total_parts);
     let mut file = std::fs::File::open(file_path)?;
     let mut chunk = Vec::with_capacity(chunk_size as usize);
 
     let upload_result = (|| -> Result<(), OneIoError> {
-        for part_number in 1..=total_parts {
+        for _ in 1..=total_parts {
             chunk.clear();
             let bytes_read = file.by_ref().take(chunk_size).read_to_end(&mut chunk)?;
             if bytes_read == 0 {
                 break;
             }
 
-            let action = bucket.upload_part(Some(&creds), key, part_number as u16, &upload_id);
-            let url = repair_leading_slash_action_url(action.sign(config.ttl), config, key, "PUT")?;
             let part_data = std::mem::replace(&mut chunk, Vec::with_capacity(chunk_size as usize));
             let part_len = part_data.len() as u64;
             let part_offset = file
@@ -517,80 +729,197 @@ fn upload_multipart(
                     ))
                 })?;
 
+            // Parts are sent in one request, so wait for their budget first.
+            if let Some(throttle) = throttle {
+                throttle.consume(part_len);
+            }
+
             // Upload this part with retry. On the first attempt, move the
             // body to avoid cloning the full chunk. On retry (transient
             // transport error), re-read the same bytes from the file.
-            let response = ensure_s3_success(upload_part_with_retry(
-                &url,
-                part_data,
-                &mut file,
-                part_offset,
-                part_len,
-            )?)?;
-
-            let etag = extract_etag(response.headers()).ok_or_else(|| {
-                OneIoError::NotSupported("Missing ETag in UploadPart response".into())
-            })?;
-            parts.push(etag);
+            upload.upload_part(part_data, || reread_part(&mut file, part_offset, part_len))?;
+            progress(part_offset + part_len, size, upload.parts_uploaded());
         }
         Ok(())
     })();
 
     if let Err(e) = upload_result {
-        abort_multipart_upload(&bucket, &creds, config, key, &upload_id);
+        upload.abort();
         return Err(e);
     }
 
     // 3. Complete multipart upload
-    let action = bucket.complete_multipart_upload(
-        Some(&creds),
-        key,
-        &upload_id,
-        parts.iter().map(|s| s.as_str()),
-    );
-    let url = repair_leading_slash_action_url(action.sign(config.ttl), config, key, "POST")?;
-    let body = action.body();
-    let response = match send_with_retry(|| {
-        get_s3_client()
-            .post(url.clone())
-            .timeout(S3_UPLOAD_REQUEST_TIMEOUT)
-            .header("content-type", "application/xml")
-            .body(body.clone())
-            .send()
-    }) {
-        Ok(response) => response,
-        Err(e) => {
-            abort_multipart_upload(&bucket, &creds, config, key, &upload_id);
-            return Err(e);
+    upload.complete()
+}
+
+/// Streaming writer that uploads to S3 while data is written.
+///
+/// Data is buffered in memory up to the multipart part size. Once a full part
+/// is buffered a multipart upload is started and each part is sent through
+/// the same retrying machinery as [`s3_upload`]. Objects that never fill a
+/// part are sent with a single PUT when the writer finishes.
+///
+/// [`finish`](S3Writer::finish) completes the upload. Dropping the writer
+/// without calling it, or any failure (including a write error), aborts the
+/// multipart upload so no partial object is published.
+///
+/// The part size starts at `ONEIO_S3_CHUNK_SIZE` (at least 5 MiB) and doubles
+/// every 1,000 parts to stay within S3's 10,000 part limit for objects of
+/// unknown size.
+pub(crate) struct S3Writer {
+    config: config::S3Config,
+    key: String,
+    base_part_size: u64,
+    part_size: u64,
+    buffer: Vec<u8>,
+    upload: Option<MultipartUpload>,
+    state: S3WriterState,
+}
+
+#[derive(PartialEq, Eq)]
+enum S3WriterState {
+    Open,
+    Finished,
+    Failed,
+}
+
+impl S3Writer {
+    /// Creates a writer for `key` in `bucket`, configured from the environment.
+    pub(crate) fn new(bucket: &str, key: &str) -> Result<Self, OneIoError> {
+        Ok(Self::with_config(config::S3Config::from_env(bucket)?, key))
+    }
+
+    fn with_config(config: config::S3Config, key: &str) -> Self {
+        let (part_size, _) = calculate_chunk_size(0, config.multipart_chunk_size);
+        Self {
+            config,
+            key: key.to_string(),
+            base_part_size: part_size,
+            part_size,
+            buffer: Vec::new(),
+            upload: None,
+            state: S3WriterState::Open,
         }
-    };
+    }
 
-    // CompleteMultipartUpload can return 200 OK with an embedded <Error> body.
-    // Validate HTTP status first, then parse the body to confirm success.
-    if !response.status().is_success() {
-        abort_multipart_upload(&bucket, &creds, config, key, &upload_id);
-        return Err(s3_error_from_response(response));
+    /// Uploads the buffered bytes as the next multipart part.
+    fn flush_part(&mut self) -> Result<(), OneIoError> {
+        let upload = match &mut self.upload {
+            Some(upload) => upload,
+            None => self
+                .upload
+                .insert(MultipartUpload::create(&self.config, &self.key)?),
+        };
+
+        // `Bytes` takes over the buffer; retries clone the handle, not the data.
+        let part = bytes::Bytes::from(std::mem::take(&mut self.buffer));
+        upload.upload_part(part.clone(), || Ok(part.clone()))?;
+
+        self.part_size = streaming_part_size(self.base_part_size, upload.parts_uploaded());
+        Ok(())
     }
-    let complete_body = response.text().unwrap_or_default();
-    if let Some(parsed) = parse_s3_error_xml(&complete_body) {
-        abort_multipart_upload(&bucket, &creds, config, key, &upload_id);
-        return Err(map_parsed_s3_error(200, parsed));
+
+    fn fail(&mut self) {
+        self.state = S3WriterState::Failed;
+        self.buffer = Vec::new();
+        if let Some(upload) = self.upload.take() {
+            upload.abort();
+        }
     }
 
-    Ok(())
+    /// Uploads any buffered data and completes the upload.
+    pub(crate) fn finish(&mut self) -> Result<(), OneIoError> {
+        match self.state {
+            S3WriterState::Finished => return Ok(()),
+            S3WriterState::Failed => {
+                return Err(OneIoError::Io(std::io::Error::other(
+                    "S3 upload was aborted after an earlier error",
+                )))
+            }
+            S3WriterState::Open => {}
+        }
+
+        let result = match self.upload.take() {
+            None => upload_single(&self.config, &self.key, std::mem::take(&mut self.buffer)),
+            Some(upload) => {
+                self.upload = Some(upload);
+                let result = if self.buffer.is_empty() {
+                    Ok(())
+                } else {
+                    self.flush_part()
+                };
+                match (result, self.upload.take()) {
+                    (Ok(()), Some(upload)) => upload.complete(),
+                    (Err(e), Some(upload)) => {
+                        upload.abort();
+                        Err(e)
+                    }
+                    (result, None) => result,
+                }
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                self.state = S3WriterState::Finished;
+                Ok(())
+            }
+            Err(e) => {
+                self.fail();
+                Err(e)
+            }
+        }
+    }
 }
 
-fn abort_multipart_upload(
-    bucket: &rusty_s3::Bucket,
-    creds: &rusty_s3::Credentials,
-    config: &config::S3Config,
-    key: &str,
-    upload_id: &str,
-) {
-    let action = bucket.abort_multipart_upload(Some(creds), key, upload_id);
-    if let Ok(url) = repair_leading_slash_action_url(action.sign(config.ttl), config, key, "DELETE")
-    {
-        let _ = get_s3_client().delete(url).send();
+/// Part size for the next part of a streaming upload of unknown length.
+///
+/// Doubles every 1,000 parts so that 10,000 parts starting at 5 MiB reach
+/// close to S3's 5 TiB object size limit.
+fn streaming_part_size(base_part_size: u64, parts_uploaded: usize) -> u64 {
+    base_part_size.saturating_mul(1 << (parts_uploaded / 1_000).min(32))
+}
+
+impl crate::writer::FinishWrite for S3Writer {
+    fn finish_write(mut self: Box<Self>) -> Result<(), OneIoError> {
+        self.finish()
+    }
+
+    fn discard(mut self: Box<Self>) {
+        self.fail();
+    }
+}
+
+impl std::io::Write for S3Writer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.state != S3WriterState::Open {
+            return Err(std::io::Error::other("S3 writer is no longer open"));
+        }
+
+        self.buffer.extend_from_slice(buf);
+        while self.buffer.len() as u64 >= self.part_size {
+            let rest = self.buffer.split_off(self.part_size as usize);
+            if let Err(e) = self.flush_part() {
+                self.fail();
+                return Err(std::io::Error::other(e));
+            }
+            self.buffer = rest;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        // Parts are sent as soon as they are full; S3 has no notion of
+        // flushing a partial part.
+        Ok(())
+    }
+}
+
+impl Drop for S3Writer {
+    fn drop(&mut self) {
+        if self.state == S3WriterState::Open {
+            self.fail();
+        }
     }
 }
 
@@ -880,22 +1209,90 @@ pub fn s3_list(
     delimiter: Option<String>,
     dirs: bool,
 ) -> Result<Vec<String>, OneIoError> {
-    let config = config::S3Config::from_env(bucket)?;
-    let bucket_obj = config.rusty_bucket()?;
-    let creds = config.rusty_credentials();
-
     let fixed_delimiter = match dirs && delimiter.is_none() {
         true => Some("/"),
         false => delimiter.as_deref(),
     };
 
     let mut result = Vec::new();
-    let mut continuation_token: Option<String> = None;
+    list_objects_pages(bucket, prefix, fixed_delimiter, |parsed| {
+        if dirs {
+            result.extend(
+                parsed
+                    .common_prefixes
+                    .into_iter()
+                    .map(|p| decode_s3_path(&p.prefix)),
+            );
+        } else {
+            result.extend(parsed.contents.into_iter().map(|c| decode_s3_path(&c.key)));
+        }
+    })?;
+    Ok(result)
+}
+
+/// Lists the objects and common prefixes directly under `prefix`.
+///
+/// Unlike [`s3_list`], entries carry object sizes and last-modified times.
+/// `scheme` (`s3` or `r2`) is used to build the entry paths.
+pub(crate) fn s3_list_entries(
+    scheme: &str,
+    bucket: &str,
+    prefix: &str,
+) -> Result<Vec<crate::ListEntry>, OneIoError> {
+    let name_of = |key: &str| {
+        key.strip_prefix(prefix)
+            .unwrap_or(key)
+            .trim_end_matches('/')
+            .to_string()
+    };
 
+    let mut entries = Vec::new();
+    list_objects_pages(bucket, prefix, Some("/"), |parsed| {
+        entries.extend(parsed.common_prefixes.into_iter().map(|p| {
+            let key = decode_s3_path(&p.prefix);
+            crate::ListEntry {
+                name: name_of(&key),
+                path: format!("{scheme}://{bucket}/{key}"),
+                size: None,
+                last_modified: None,
+                is_dir: true,
+            }
+        }));
+        entries.extend(
+            parsed
+                .contents
+                .into_iter()
+                .map(|c| (decode_s3_path(&c.key), c))
+                // Zero-byte "directory marker" objects name the prefix itself.
+                .filter(|(key, _)| key != prefix)
+                .map(|(key, c)| crate::ListEntry {
+                    name: name_of(&key),
+                    path: format!("{scheme}://{bucket}/{key}"),
+                    size: Some(c.size),
+                    last_modified: Some(crate::listing::trim_fractional_seconds(&c.last_modified)),
+                    is_dir: false,
+                }),
+        );
+    })?;
+    Ok(entries)
+}
+
+/// Runs a paginated `ListObjectsV2` request, passing every page to `on_page`.
+fn list_objects_pages(
+    bucket: &str,
+    prefix: &str,
+    delimiter: Option<&str>,
+    mut on_page: impl FnMut(rusty_s3::actions::ListObjectsV2Response),
+) -> Result<(), OneIoError> {
+    let config = config::S3Config::from_env(bucket)?;
+    let bucket_obj = config.rusty_bucket()?;
+    let creds = config.rusty_credentials();
+
+    let mut continuation_token: Option<String> = None;
     loop {
         let mut action = bucket_obj.list_objects_v2(Some(&creds));
         action.with_prefix(prefix);
-        if let Some(delim) = fixed_delimiter {
+        if let Some(delim) = delimiter {
             action.with_delimiter(delim);
         }
         if let Some(token) = &continuation_token {
@@ -907,25 +1304,12 @@ pub fn s3_list(
 
         let parsed = rusty_s3::actions::ListObjectsV2::parse_response(response.text()?.as_bytes())
             .map_err(|e| OneIoError::Network(Box::new(e)))?;
-
-        if dirs {
-            result.extend(
-                parsed
-                    .common_prefixes
-                    .into_iter()
-                    .map(|p| decode_s3_path(&p.prefix)),
-            );
-        } else {
-            result.extend(parsed.contents.into_iter().map(|c| decode_s3_path(&c.key)));
-        }
-
-        match parsed.next_continuation_token {
-            Some(token) => continuation_token = Some(token),
-            None => break,
+        continuation_token = parsed.next_continuation_token.clone();
+        on_page(parsed);
+        if continuation_token.is_none() {
+            return Ok(());
         }
     }
-
-    Ok(result)
 }
 
 /// Check an S3 HTTP response for errors and preserve the response body for callers.
@@ -1080,6 +1464,105 @@ mod tests {
         }
     }
 
+    /// Serves S3 multipart requests on localhost, recording the method and
+    /// path of each request.
+    fn spawn_multipart_server() -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                std::io::copy(
+                    &mut reader.by_ref().take(content_length),
+                    &mut std::io::sink(),
+                )
+                .unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                let body = if method == "POST" && path.contains("uploads") {
+                    "<InitiateMultipartUploadResult><Bucket>test-bucket</Bucket>\
+                     <Key>key</Key><UploadId>upload-1</UploadId>\
+                     </InitiateMultipartUploadResult>"
+                } else {
+                    ""
+                };
+                log.lock().unwrap().push(format!("{method} {path}"));
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"etag-1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = reader.get_mut().write_all(response.as_bytes());
+            }
+        });
+        (endpoint, requests)
+    }
+
+    #[test]
+    fn test_s3_writer_drop_without_finish_aborts() {
+        use std::io::Write;
+
+        let (endpoint, requests) = spawn_multipart_server();
+        let config = config::S3Config {
+            endpoint,
+            multipart_chunk_size: 5 * 1024 * 1024,
+            ..path_style_test_config()
+        };
+
+        // Nothing is sent for an object that never filled a part.
+        let mut writer = S3Writer::with_config(config.clone(), "small");
+        writer.write_all(b"partial").unwrap();
+        drop(writer);
+        assert!(requests.lock().unwrap().is_empty());
+
+        // A started multipart upload is aborted instead of completed.
+        let mut writer = S3Writer::with_config(config, "large");
+        writer.write_all(&vec![0; 5 * 1024 * 1024 + 1]).unwrap();
+        drop(writer);
+        let methods: Vec<String> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| request.split(' ').next().unwrap().to_string())
+            .collect();
+        assert_eq!(methods, ["POST", "PUT", "DELETE"]);
+    }
+
+    #[test]
+    fn test_streaming_part_size_covers_max_object_size() {
+        let base = 5 * 1024 * 1024;
+        assert_eq!(streaming_part_size(base, 0), base);
+        assert_eq!(streaming_part_size(base, 999), base);
+        assert_eq!(streaming_part_size(base, 1_000), base * 2);
+        assert_eq!(streaming_part_size(base, 2_500), base * 4);
+
+        let total: u64 = (0..10_000)
+            .map(|part| streaming_part_size(base, part))
+            .sum();
+        assert!(total >= 5 * 1024 * 1024 * 1024 * 1024 * 95 / 100);
+    }
+
     #[test]
     fn test_leading_slash_path_style_urls_preserve_bucket_and_key() {
         let config = path_style_test_config();
@@ -0,0 +1,388 @@
+//! Random-access readers over local files and ranged remote reads.
+//!
+//! Local paths are served by a buffered [`File`]. Remote paths are served by
+//! [`Backend::read_range`]: the reader keeps a small read-ahead buffer and
+//! reuses the open ranged stream while reads stay sequential, so only seeks
+//! outside the buffer cost a new request.
+
+use crate::backend::Backend;
+use crate::rate_limit::RateLimiter;
+use crate::OneIoError;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Size of the read-ahead buffer used for remote seekable readers.
+const READ_AHEAD_SIZE: usize = 64 * 1024;
+
+/// A reader supporting [`Read`] and [`Seek`], returned by
+/// [`OneIo::get_seekable_reader`](crate::OneIo::get_seekable_reader).
+///
+/// Data is read as stored; no decompression is applied.
+pub struct SeekableReader {
+    inner: Inner,
+}
+
+enum Inner {
+    Local { file: BufReader<File>, len: u64 },
+    Remote(RangeReader),
+}
+
+impl SeekableReader {
+    /// Opens a local file for random access.
+    pub(crate) fn local(path: &Path) -> Result<Self, OneIoError> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            inner: Inner::Local {
+                file: BufReader::with_capacity(READ_AHEAD_SIZE, file),
+                len,
+            },
+        })
+    }
+
+    /// Opens a remote path for random access through `backend`, limiting
+    /// every ranged read with `rate_limiter`.
+    ///
+    /// The first read-ahead block is fetched eagerly, so a backend or server
+    /// without range support fails here with
+    /// [`OneIoError::RangeNotSupported`] rather than on first read.
+    pub(crate) fn remote(
+        backend: Arc<dyn Backend>,
+        path: &str,
+        rate_limiter: RateLimiter,
+    ) -> Result<Self, OneIoError> {
+        let mut buf = Vec::with_capacity(READ_AHEAD_SIZE);
+        let first = backend.read_range(path, 0, Some(READ_AHEAD_SIZE as u64))?;
+        rate_limiter.reader(path, first).read_to_end(&mut buf)?;
+
+        // A short first block is the whole object; otherwise ask the backend.
+        let len = if buf.len() < READ_AHEAD_SIZE {
+            Some(buf.len() as u64)
+        } else {
+            backend.stat(path).ok().and_then(|stat| stat.content_length)
+        };
+
+        Ok(Self {
+            inner: Inner::Remote(RangeReader {
+                backend,
+                path: path.to_string(),
+                rate_limiter,
+                len,
+                pos: 0,
+                buf,
+                buf_start: 0,
+                stream: None,
+            }),
+        })
+    }
+
+    /// Total length of the underlying object in bytes, if known.
+    pub fn content_length(&self) -> Option<u64> {
+        match &self.inner {
+            Inner::Local { len, .. } => Some(*len),
+            Inner::Remote(reader) => reader.len,
+        }
+    }
+}
+
+impl Read for SeekableReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Inner::Local { file, .. } => file.read(out),
+            Inner::Remote(reader) => reader.read(out),
+        }
+    }
+}
+
+impl Seek for SeekableReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &mut self.inner {
+            Inner::Local { file, .. } => file.seek(pos),
+            Inner::Remote(reader) => reader.seek(pos),
+        }
+    }
+}
+
+/// Remote reader issuing ranged reads around a read-ahead buffer.
+struct RangeReader {
+    backend: Arc<dyn Backend>,
+    path: String,
+    rate_limiter: RateLimiter,
+    len: Option<u64>,
+    /// Current logical position.
+    pos: u64,
+    /// Buffered bytes starting at `buf_start`.
+    buf: Vec<u8>,
+    buf_start: u64,
+    /// Open-ended ranged stream and the offset of its next byte.
+    stream: Option<(Box<dyn Read + Send>, u64)>,
+}
+
+impl RangeReader {
+    /// Refills the buffer starting at the current position. Returns the
+    /// number of bytes buffered; zero means end of object.
+    fn fill(&mut self) -> io::Result<usize> {
+        let (mut stream, stream_pos) = match self.stream.take() {
+            Some((stream, stream_pos)) if stream_pos == self.pos => (stream, stream_pos),
+            _ => {
+                let stream = self
+                    .backend
+                    .read_range(&self.path, self.pos, None)
+                    .map_err(io::Error::other)?;
+                (self.rate_limiter.reader(&self.path, stream), self.pos)
+            }
+        };
+
+        self.buf.clear();
+        stream
+            .by_ref()
+            .take(READ_AHEAD_SIZE as u64)
+            .read_to_end(&mut self.buf)?;
+        self.buf_start = stream_pos;
+
+        let n = self.buf.len();
+        if n > 0 {
+            self.stream = Some((stream, stream_pos + n as u64));
+        }
+        Ok(n)
+    }
+
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() || self.len.is_some_and(|len| self.pos >= len) {
+            return Ok(0);
+        }
+
+        let buf_end = self.buf_start + self.buf.len() as u64;
+        if !(self.buf_start..buf_end).contains(&self.pos) && self.fill()? == 0 {
+            return Ok(0);
+        }
+
+        let offset = (self.pos - self.buf_start) as usize;
+        let n = out.len().min(self.buf.len() - offset);
+        out[..n].copy_from_slice(&self.buf[offset..offset + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(delta) => {
+                let len = self.len.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!("cannot seek from end, length unknown: {}", self.path),
+                    )
+                })?;
+                len.checked_add_signed(delta)
+            }
+        };
+
+        self.pos = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+/// Formats an HTTP `Range` header value for `len` bytes from `offset`.
+///
+/// Ranges reaching past `u64::MAX` end at `u64::MAX`, which servers clamp to
+/// the end of the object.
+#[cfg(feature = "http")]
+pub(crate) fn range_header(offset: u64, len: Option<u64>) -> String {
+    match len {
+        Some(len) => format!("bytes={offset}-{}", offset.saturating_add(len.max(1) - 1)),
+        None => format!("bytes={offset}-"),
+    }
+}
+
+/// Turns the response to a ranged GET into a reader over the requested bytes.
+///
+/// `206` responses are checked against the requested start offset. A `416`
+/// at or past the end of the object is an empty range. A `200` is only
+/// accepted for an open-ended read from offset zero; otherwise the server
+/// ignored the `Range` header and [`OneIoError::RangeNotSupported`] is
+/// returned. Other statuses are mapped with `on_error`.
+#[cfg(feature = "http")]
+pub(crate) fn range_response_reader(
+    response: reqwest::blocking::Response,
+    path: &str,
+    offset: u64,
+    len: Option<u64>,
+    on_error: impl FnOnce(reqwest::blocking::Response) -> OneIoError,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    use crate::resumable_http::{parse_content_range_start, parse_content_range_total};
+    use reqwest::StatusCode;
+
+    let content_range = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => {
+            let start = content_range.as_deref().and_then(parse_content_range_start);
+            if start != Some(offset) {
+                return Err(OneIoError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{path}: ranged response does not start at byte {offset} \
+                         (Content-Range: {})",
+                        content_range.as_deref().unwrap_or("missing")
+                    ),
+                )));
+            }
+            Ok(match len {
+                Some(len) => Box::new(response.take(len)),
+                None => Box::new(response),
+            })
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            let total = content_range.as_deref().and_then(parse_content_range_total);
+            match total {
+                Some(total) if offset >= total => Ok(Box::new(io::empty())),
+                _ => Err(on_error(response)),
+            }
+        }
+        StatusCode::OK if offset == 0 && len.is_none() => Ok(Box::new(response)),
+        status if status.is_success() => Err(OneIoError::RangeNotSupported(path.to_string())),
+        _ => Err(on_error(response)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ObjectStat;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// In-memory backend counting the ranged reads it serves.
+    struct RangeBackend {
+        data: Vec<u8>,
+        requests: AtomicUsize,
+    }
+
+    impl RangeBackend {
+        fn new(size: usize) -> Arc<Self> {
+            Arc::new(Self {
+                data: (0..size).map(|i| (i % 251) as u8).collect(),
+                requests: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    impl Backend for RangeBackend {
+        fn reader_raw(&self, _path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+            Ok(Box::new(io::Cursor::new(self.data.clone())))
+        }
+
+        fn stat(&self, _path: &str) -> Result<ObjectStat, OneIoError> {
+            Ok(ObjectStat {
+                content_length: Some(self.data.len() as u64),
+                ..Default::default()
+            })
+        }
+
+        fn read_range(
+            &self,
+            _path: &str,
+            offset: u64,
+            len: Option<u64>,
+        ) -> Result<Box<dyn Read + Send>, OneIoError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let start = (offset as usize).min(self.data.len());
+            let end = len.map_or(self.data.len(), |len| {
+                (start + len as usize).min(self.data.len())
+            });
+            Ok(Box::new(io::Cursor::new(self.data[start..end].to_vec())))
+        }
+    }
+
+    #[test]
+    fn test_remote_sequential_read_reuses_stream() {
+        let backend = RangeBackend::new(READ_AHEAD_SIZE * 3 + 17);
+        let mut reader =
+            SeekableReader::remote(backend.clone(), "mem://data", RateLimiter::default()).unwrap();
+        assert_eq!(reader.content_length(), Some(backend.data.len() as u64));
+
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert_eq!(content, backend.data);
+        // Probe plus a single open-ended stream for the rest.
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_remote_seek_and_read() {
+        let backend = RangeBackend::new(READ_AHEAD_SIZE * 4);
+        let mut reader =
+            SeekableReader::remote(backend.clone(), "mem://data", RateLimiter::default()).unwrap();
+        let mut buf = [0u8; 16];
+
+        for pos in [
+            SeekFrom::Start(100_000),
+            SeekFrom::End(-16),
+            SeekFrom::Start(5),
+            SeekFrom::Current(1_000),
+        ] {
+            let offset = reader.seek(pos).unwrap() as usize;
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(&buf, &backend.data[offset..offset + 16], "{pos:?}");
+        }
+
+        reader.seek(SeekFrom::End(0)).unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert!(reader.seek(SeekFrom::Current(-1_000_000)).is_err());
+    }
+
+    #[test]
+    fn test_remote_small_object_is_fully_buffered() {
+        let backend = RangeBackend::new(10);
+        let mut reader =
+            SeekableReader::remote(backend.clone(), "mem://data", RateLimiter::default()).unwrap();
+        assert_eq!(reader.content_length(), Some(10));
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert_eq!(content, backend.data[4..]);
+        assert_eq!(backend.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_remote_without_range_support() {
+        struct NoRanges;
+        impl Backend for NoRanges {
+            fn reader_raw(&self, _path: &str) -> Result<Box<dyn Read + Send>, OneIoError> {
+                Ok(Box::new(io::empty()))
+            }
+        }
+
+        let result =
+            SeekableReader::remote(Arc::new(NoRanges), "mem://data", RateLimiter::default());
+        assert!(matches!(result, Err(OneIoError::RangeNotSupported(_))));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_range_header() {
+        assert_eq!(range_header(0, Some(10)), "bytes=0-9");
+        assert_eq!(range_header(5, None), "bytes=5-");
+        assert_eq!(
+            range_header(u64::MAX - 1, Some(2)),
+            format!("bytes={}-{}", u64::MAX - 1, u64::MAX)
+        );
+        assert_eq!(
+            range_header(10, Some(u64::MAX)),
+            format!("bytes=10-{}", u64::MAX)
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Tar archive support (feature `archive`).
+//!
+//! Tar archives are read front to back as a stream, so they work over any
+//! backend (HTTP, S3, ...) without seeking or temporary files, and reading
+//! stops as soon as the requested member has been consumed.
+//!
+//! Headers are parsed by the `tar` crate, which handles ustar and pre-POSIX
+//! headers, GNU long names, PAX extended headers and base-256 sizes. Each
+//! member header is read with a fresh [`tar::Archive`] over the shared
+//! stream, so the data of the matching member can be handed out as an owned
+//! reader.
+
+use crate::archive::{ArchiveEntry, ArchiveEntryKind};
+use crate::OneIoError;
+use std::io::Read;
+use tar::EntryType;
+
+const BLOCK_SIZE: u64 = 512;
+
+/// Whether `path` names a tar archive, optionally compressed.
+pub(crate) fn is_tar_path(path: &str) -> bool {
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let name = path.rsplit('/').next().unwrap_or(path).to_ascii_lowercase();
+    if [".tar", ".tgz", ".tbz", ".tbz2", ".txz", ".tzst", ".tlz4"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        return true;
+    }
+    name.rsplit_once('.')
+        .is_some_and(|(stem, _)| stem.ends_with(".tar"))
+}
+
+/// Normalizes a member path for comparison: no leading `./` or `/`, no
+/// trailing `/`.
+fn normalize_member_path(path: &str) -> &str {
+    let mut path = path;
+    loop {
+        if let Some(rest) = path.strip_prefix("./") {
+            path = rest;
+        } else if let Some(rest) = path.strip_prefix('/') {
+            path = rest;
+        } else {
+            break;
+        }
+    }
+    path.trim_end_matches('/')
+}
+
+/// Reads the data of `member` from the tar stream `archive`.
+///
+/// Returns a reader limited to the member's size, positioned at its first
+/// byte. Fails with a `NotFound` IO error if the archive has no such member.
+pub(crate) fn member_reader(
+    archive: Box<dyn Read + Send>,
+    archive_path: &str,
+    member: &str,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    let wanted = normalize_member_path(member);
+    let mut stream = TarStream::new(archive);
+    while let Some(entry) = stream.next_entry()? {
+        if normalize_member_path(&entry.path) == wanted && entry.kind == ArchiveEntryKind::File {
+            return Ok(Box::new(stream.take(entry.size)));
+        }
+    }
+    Err(OneIoError::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("no member '{member}' in archive {archive_path}"),
+    )))
+}
+
+/// Iterator over the members of a tar stream.
+///
+/// Stops after the end-of-archive marker or the first error.
+pub(crate) struct ArchiveEntries {
+    stream: Option<TarStream>,
+}
+
+impl ArchiveEntries {
+    pub(crate) fn new(archive: Box<dyn Read + Send>) -> Self {
+        Self {
+            stream: Some(TarStream::new(archive)),
+        }
+    }
+}
+
+impl Iterator for ArchiveEntries {
+    type Item = Result<ArchiveEntry, OneIoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.stream.as_mut()?.next_entry();
+        match result {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.stream = None;
+                None
+            }
+            Err(e) => {
+                self.stream = None;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// A tar stream read one member header at a time.
+struct TarStream {
+    reader: Box<dyn Read + Send>,
+    /// Bytes read from `reader` so far.
+    pos: u64,
+    /// Stream offset of the next header, past the current member's data.
+    next_header: u64,
+}
+
+impl TarStream {
+    fn new(reader: Box<dyn Read + Send>) -> Self {
+        Self {
+            reader,
+            pos: 0,
+            next_header: 0,
+        }
+    }
+
+    /// Returns the next member, skipping the data of the previous one. The
+    /// member's data is left unread.
+    fn next_entry(&mut self) -> Result<Option<ArchiveEntry>, OneIoError> {
+        loop {
+            let pending = self.next_header - self.pos;
+            if std::io::copy(&mut self.by_ref().take(pending), &mut std::io::sink())? < pending {
+                return Err(OneIoError::Io(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "truncated tar archive",
+                )));
+            }
+
+            let (entry_type, path, stored) = {
+                let mut archive = tar::Archive::new(&mut *self);
+                let Some(entry) = archive.entries()?.next().transpose()? else {
+                    return Ok(None);
+                };
+                let entry_type = entry.header().entry_type();
+                let path = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+                // Sparse members store less data than their logical size.
+                let stored = match entry_type {
+                    EntryType::GNUSparse => entry.header().entry_size()?,
+                    _ => entry.size(),
+                };
+                (entry_type, path, stored)
+            };
+            self.next_header = (self.pos + stored).div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+
+            let kind = match entry_type {
+                // Global PAX headers and volume labels describe no member.
+                EntryType::XGlobalHeader => continue,
+                _ if entry_type.as_byte() == b'V' => continue,
+                // Pre-POSIX archives mark directories by a trailing slash.
+                EntryType::Regular if path.ends_with('/') => ArchiveEntryKind::Directory,
+                EntryType::Regular | EntryType::Continuous => ArchiveEntryKind::File,
+                EntryType::Directory => ArchiveEntryKind::Directory,
+                EntryType::Symlink => ArchiveEntryKind::Symlink,
+                EntryType::Link => ArchiveEntryKind::HardLink,
+                _ => ArchiveEntryKind::Other,
+            };
+            let size = match kind {
+                ArchiveEntryKind::File => stored,
+                _ => 0,
+            };
+            return Ok(Some(ArchiveEntry { path, size, kind }));
+        }
+    }
+}
+
+impl Read for TarStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::{split_archive_path, ArchiveFormat};
+
+    fn build(append: impl FnOnce(&mut tar::Builder<Vec<u8>>)) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        append(&mut builder);
+        builder.into_inner().unwrap()
+    }
+
+    fn file(builder: &mut tar::Builder<Vec<u8>>, path: &str, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, path, data).unwrap();
+    }
+
+    fn read_member(archive: &[u8], name: &str) -> Result<String, OneIoError> {
+        let archive = Box::new(std::io::Cursor::new(archive.to_vec()));
+        let mut content = String::new();
+        member_reader(archive, "test.tar", name)?.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    fn list(archive: &[u8]) -> Result<Vec<ArchiveEntry>, OneIoError> {
+        ArchiveEntries::new(Box::new(std::io::Cursor::new(archive.to_vec()))).collect()
+    }
+
+    #[test]
+    fn splits_only_tar_paths() {
+        let tar = ArchiveFormat::Tar;
+        assert_eq!(
+            split_archive_path("https://host/data.tar.gz!/inner/file.csv"),
+            Some(("https://host/data.tar.gz", "inner/file.csv", tar))
+        );
+        for archive in ["a.tgz", "a.tar", "a.tar.zst", "a.tbz2", "a.txz", "a.tlz4"] {
+            assert_eq!(
+                split_archive_path(&format!("{archive}!/b")),
+                Some((archive, "b", tar))
+            );
+        }
+        assert_eq!(split_archive_path("https://host/weird!/path.gz"), None);
+        assert_eq!(split_archive_path("data.tar.gz"), None);
+    }
+
+    #[test]
+    fn reads_members_with_long_names_and_pax_headers() {
+        let long_name = format!("{}/file.txt", "d".repeat(150));
+        let archive = build(|builder| {
+            let mut dir = tar::Header::new_gnu();
+            dir.set_entry_type(EntryType::Directory);
+            dir.set_size(0);
+            builder
+                .append_data(&mut dir, "dir/", std::io::empty())
+                .unwrap();
+            file(builder, "dir/a.txt", b"first");
+            file(builder, &long_name, b"long");
+            builder
+                .append_pax_extensions([("path", "pax/ünïcode.txt".as_bytes())])
+                .unwrap();
+            file(builder, "ignored", b"pax");
+        });
+
+        let entries = list(&archive).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(
+            names,
+            ["dir/", "dir/a.txt", long_name.as_str(), "pax/ünïcode.txt"]
+        );
+        assert_eq!(entries[0].kind, ArchiveEntryKind::Directory);
+        assert_eq!(entries[1].size, 5);
+        assert_eq!(read_member(&archive, "./dir/a.txt").unwrap(), "first");
+        assert_eq!(read_member(&archive, &long_name).unwrap(), "long");
+        assert_eq!(read_member(&archive, "pax/ünïcode.txt").unwrap(), "pax");
+    }
+
+    #[test]
+    fn reports_missing_members_and_invalid_archives() {
+        let archive = build(|builder| file(builder, "a.txt", b"a"));
+        let err = read_member(&archive, "b.txt").unwrap_err();
+        assert!(matches!(err, OneIoError::Io(e) if e.kind() == std::io::ErrorKind::NotFound));
+
+        assert!(read_member(&[b'x'; 1024], "a.txt").is_err());
+
+        let truncated = build(|builder| file(builder, "a.txt", &[b'a'; 2000]));
+        assert!(list(&truncated[..1024]).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Writer handle returned by [`OneIo::get_writer`](crate::OneIo::get_writer).
+//!
+//! Every layer of a writer stack (compression encoder, atomic temp file, S3
+//! upload, plain buffered file) implements [`FinishWrite`], so
+//! [`OneIoWriter::finish`] can finalize the whole stack from the outside in
+//! and report the first error instead of losing it in `Drop`. A writer
+//! dropped without `finish` is [discarded](FinishWrite::discard) instead, so
+//! destinations that publish data on finish never publish a partial write.
+
+use crate::OneIoError;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// A writer layer that can be finalized with error reporting.
+pub(crate) trait FinishWrite: Write + Send {
+    /// Writes any trailing data, then finalizes the inner writer.
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError>;
+
+    /// Abandons a writer dropped without being finished, ignoring errors.
+    ///
+    /// Layers that publish data on finish (atomic renames, S3 uploads)
+    /// discard it instead.
+    /// The default finalizes on a best-effort basis, like dropping a
+    /// [`BufWriter`].
+    fn discard(self: Box<Self>) {
+        let _ = self.finish_write();
+    }
+}
+
+impl FinishWrite for BufWriter<File> {
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+        self.into_inner().map_err(|e| e.into_error())?;
+        Ok(())
+    }
+}
+
+/// Writer returned by [`OneIo::get_writer`](crate::OneIo::get_writer).
+///
+/// Call [`finish`](OneIoWriter::finish) once all data is written: it writes
+/// the compression trailer, flushes buffers, commits atomic writes and
+/// completes S3 uploads, returning any error on the way. Dropping the writer
+/// without calling `finish` discards atomic writes, S3 uploads and custom
+/// backend writes, and finalizes plain local files on a best-effort basis,
+/// ignoring errors. `discard` is skipped while panicking; atomic writes and
+/// S3 uploads are still abandoned.
+pub struct OneIoWriter {
+    inner: Option<Box<dyn FinishWrite>>,
+}
+
+impl OneIoWriter {
+    pub(crate) fn new(inner: Box<dyn FinishWrite>) -> Self {
+        Self { inner: Some(inner) }
+    }
+
+    /// Wraps a plain writer, for example in
+    /// [`Backend::writer_raw`](crate::Backend::writer_raw). Finishing the
+    /// writer flushes it.
+    pub fn from_writer<W: Write + Send + 'static>(writer: W) -> Self {
+        Self::with_finish(writer, |mut writer| Ok(writer.flush()?))
+    }
+
+    /// Wraps a writer whose data is committed by `finish`, for example by
+    /// completing an upload. `finish` runs when the returned writer is
+    /// [finished](OneIoWriter::finish), after a final flush.
+    pub fn with_finish<W, F>(writer: W, finish: F) -> Self
+    where
+        W: Write + Send + 'static,
+        F: FnOnce(W) -> Result<(), OneIoError> + Send + 'static,
+    {
+        Self::new(Box::new(WithFinish { writer, finish }))
+    }
+
+    /// Finalizes the writer, reporting any error from the codec, the final
+    /// flush or the destination.
+    pub fn finish(mut self) -> Result<(), OneIoError> {
+        match self.inner.take() {
+            Some(inner) => inner.finish_write(),
+            None => Ok(()),
+        }
+    }
+
+    fn inner(&mut self) -> std::io::Result<&mut Box<dyn FinishWrite>> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| std::io::Error::other("writer already finished"))
+    }
+}
+
+impl Write for OneIoWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner()?.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.inner()?.write_all(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner()?.flush()
+    }
+}
+
+impl FinishWrite for OneIoWriter {
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+        self.finish()
+    }
+
+    fn discard(self: Box<Self>) {
+        // Dropping discards the inner stack.
+    }
+}
+
+impl Drop for OneIoWriter {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            if !std::thread::panicking() {
+                inner.discard();
+            }
+        }
+    }
+}
+
+/// Writer paired with the step that commits its data.
+struct WithFinish<W, F> {
+    writer: W,
+    finish: F,
+}
+
+impl<W: Write, F> Write for WithFinish<W, F> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W, F> FinishWrite for WithFinish<W, F>
+where
+    W: Write + Send,
+    F: FnOnce(W) -> Result<(), OneIoError> + Send,
+{
+    fn finish_write(self: Box<Self>) -> Result<(), OneIoError> {
+        let WithFinish { mut writer, finish } = *self;
+        writer.flush()?;
+        finish(writer)
+    }
+
+    fn discard(self: Box<Self>) {
+        // The data is only committed by an explicit finish.
+    }
+}
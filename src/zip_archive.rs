@@ -0,0 +1,261 @@
+//! Zip archive support (feature `zip`).
+//!
+//! Zip archives keep their index (the central directory) at the end of the
+//! file, so they are opened through a [`SeekableReader`]: local files are read
+//! directly and remote archives fetch the central directory and the requested
+//! member with ranged requests instead of downloading the whole archive.
+//! Servers without range support fall back to buffering the archive in memory.
+//!
+//! Member data is decoded with oneio's own codecs: stored members always
+//! work, deflate, bzip2, zstd and xz members need the matching codec feature.
+//! The CRC-32 and size recorded in the central directory are checked once a
+//! member has been read to the end.
+
+use crate::archive::{ArchiveEntry, ArchiveEntryKind};
+use crate::client::OneIo;
+use crate::seekable::SeekableReader;
+use crate::OneIoError;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use zip::{CompressionMethod, ZipArchive};
+
+/// Whether `path` names a zip archive.
+pub(crate) fn is_zip_path(path: &str) -> bool {
+    crate::file_extension(path).eq_ignore_ascii_case("zip")
+}
+
+/// Lists the entries of the zip archive at `path` from its central directory.
+pub(crate) fn list(oneio: &OneIo, path: &str) -> Result<Vec<ArchiveEntry>, OneIoError> {
+    match open_seekable(oneio, path)? {
+        Source::Seekable(reader) => list_entries(ZipArchive::new(reader)?),
+        Source::Buffered(reader) => list_entries(ZipArchive::new(reader)?),
+    }
+}
+
+/// Opens `member` of the zip archive at `path`, decoding its zip compression.
+///
+/// Fails with a `NotFound` IO error if the archive has no such member.
+pub(crate) fn member_reader(
+    oneio: &OneIo,
+    path: &str,
+    member: &str,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    match open_seekable(oneio, path)? {
+        Source::Seekable(reader) => named_member(ZipArchive::new(reader)?, path, member),
+        Source::Buffered(reader) => named_member(ZipArchive::new(reader)?, path, member),
+    }
+}
+
+/// Opens the zip archive at `path` for reading without a member.
+///
+/// The archive is fetched once: if it holds no file or several files, the
+/// already opened source is rewound and returned as the raw archive bytes.
+pub(crate) fn single_file_reader(oneio: &OneIo, path: &str) -> Result<Opened, OneIoError> {
+    match open_seekable(oneio, path)? {
+        Source::Seekable(reader) => only_file(ZipArchive::new(reader)?, path),
+        Source::Buffered(reader) => only_file(ZipArchive::new(reader)?, path),
+    }
+}
+
+/// Like [`single_file_reader`], for an already opened local archive.
+pub(crate) fn single_file_from_local(file: File, path: &str) -> Result<Opened, OneIoError> {
+    only_file(ZipArchive::new(file)?, path)
+}
+
+/// What a zip path without a member opens to.
+pub(crate) enum Opened {
+    /// The archive's only file: its name and decoded data.
+    File(String, Box<dyn Read + Send>),
+    /// The archive itself, as raw bytes from the start.
+    Archive(Box<dyn Read + Send>),
+}
+
+enum Source {
+    Seekable(SeekableReader),
+    Buffered(std::io::Cursor<Vec<u8>>),
+}
+
+fn open_seekable(oneio: &OneIo, path: &str) -> Result<Source, OneIoError> {
+    match oneio.get_seekable_reader(path) {
+        Ok(reader) => Ok(Source::Seekable(reader)),
+        Err(OneIoError::RangeNotSupported(_)) => {
+            let mut bytes = Vec::new();
+            oneio.get_reader_raw(path)?.read_to_end(&mut bytes)?;
+            Ok(Source::Buffered(std::io::Cursor::new(bytes)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn named_member<R: Read + Seek + Send + 'static>(
+    archive: ZipArchive<R>,
+    path: &str,
+    member: &str,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    let index = archive
+        .index_for_name(member.trim_start_matches("./").trim_start_matches('/'))
+        .ok_or_else(|| {
+            OneIoError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no member '{member}' in archive {path}"),
+            ))
+        })?;
+    let (_, reader) = open_member(archive, path, index)?;
+    Ok(reader)
+}
+
+fn only_file<R: Read + Seek + Send + 'static>(
+    mut archive: ZipArchive<R>,
+    path: &str,
+) -> Result<Opened, OneIoError> {
+    match single_file_index(&mut archive)? {
+        Some(index) => {
+            let (name, reader) = open_member(archive, path, index)?;
+            Ok(Opened::File(name, reader))
+        }
+        None => {
+            let mut reader = archive.into_inner();
+            reader.seek(SeekFrom::Start(0))?;
+            Ok(Opened::Archive(Box::new(reader)))
+        }
+    }
+}
+
+fn list_entries<R: Read + Seek>(
+    mut archive: ZipArchive<R>,
+) -> Result<Vec<ArchiveEntry>, OneIoError> {
+    (0..archive.len())
+        .map(|index| {
+            let file = archive.by_index_raw(index)?;
+            let kind = if file.is_dir() {
+                ArchiveEntryKind::Directory
+            } else if file.is_symlink() {
+                ArchiveEntryKind::Symlink
+            } else {
+                ArchiveEntryKind::File
+            };
+            Ok(ArchiveEntry {
+                path: file.name().to_string(),
+                size: file.size(),
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Opens the member at `index` and returns its name and decoded data.
+fn open_member<R: Read + Seek + Send + 'static>(
+    mut archive: ZipArchive<R>,
+    path: &str,
+    index: usize,
+) -> Result<(String, Box<dyn Read + Send>), OneIoError> {
+    let (name, method, data_start, compressed_size, size, crc32) = {
+        let file = archive.by_index_raw(index)?;
+        if file.encrypted() {
+            return Err(OneIoError::NotSupported(format!(
+                "encrypted zip member '{}' in {path}",
+                file.name()
+            )));
+        }
+        let data_start = file.data_start().ok_or_else(|| {
+            OneIoError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("zip member '{}' has no data offset", file.name()),
+            ))
+        })?;
+        (
+            file.name().to_string(),
+            file.compression(),
+            data_start,
+            file.compressed_size(),
+            file.size(),
+            file.crc32(),
+        )
+    };
+
+    let mut reader = archive.into_inner();
+    reader.seek(SeekFrom::Start(data_start))?;
+    let data: Box<dyn Read + Send> = Box::new(reader.take(compressed_size));
+    let decoded = decode_member(data, method)?;
+    Ok((
+        name.clone(),
+        Box::new(CheckedReader {
+            inner: decoded,
+            name,
+            hasher: crc32fast::Hasher::new(),
+            expected_crc32: crc32,
+            expected_size: size,
+            read: 0,
+        }),
+    ))
+}
+
+/// Verifies the CRC-32 and size of a member when its data ends.
+struct CheckedReader {
+    inner: Box<dyn Read + Send>,
+    name: String,
+    hasher: crc32fast::Hasher,
+    expected_crc32: u32,
+    expected_size: u64,
+    read: u64,
+}
+
+impl Read for CheckedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.hasher.update(&buf[..n]);
+            self.read += n as u64;
+        } else if !buf.is_empty() {
+            let crc32 = self.hasher.clone().finalize();
+            if self.read != self.expected_size || crc32 != self.expected_crc32 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "zip member '{}' is corrupt: read {} bytes with CRC-32 {crc32:08x}, expected {} bytes with {:08x}",
+                        self.name, self.read, self.expected_size, self.expected_crc32
+                    ),
+                ));
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// Returns the index of the only file in the archive, if it holds exactly
+/// one.
+fn single_file_index<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<usize>, OneIoError> {
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        if !archive.by_index_raw(index)?.is_dir() {
+            files.push(index);
+        }
+    }
+    Ok(match files.as_slice() {
+        [index] => Some(*index),
+        _ => None,
+    })
+}
+
+/// Wraps raw member data in the decoder for zip compression `method`.
+fn decode_member(
+    data: Box<dyn Read + Send>,
+    method: CompressionMethod,
+) -> Result<Box<dyn Read + Send>, OneIoError> {
+    match method {
+        CompressionMethod::STORE => Ok(data),
+        #[cfg(feature = "any_gz")]
+        CompressionMethod::DEFLATE => Ok(Box::new(flate2::read::DeflateDecoder::new(data))),
+        #[cfg(feature = "bz")]
+        CompressionMethod::BZIP2 => Ok(Box::new(bzip2::read::BzDecoder::new(data))),
+        #[cfg(feature = "zstd")]
+        CompressionMethod::ZSTD => Ok(Box::new(::zstd::Decoder::new(data)?)),
+        #[cfg(feature = "xz")]
+        CompressionMethod::XZ => Ok(Box::new(xz2::read::XzDecoder::new(data))),
+        method => Err(OneIoError::NotSupported(format!(
+            "zip compression method {method:?} (enable the matching codec feature)"
+        ))),
+    }
+}
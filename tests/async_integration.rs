@@ -28,6 +28,52 @@ async fn async_read_local_gzip() {
     assert_eq!(content, TEST_TEXT);
 }
 
+#[cfg(feature = "any_gz")]
+#[tokio::test]
+async fn async_read_local_gzip_multi_member() {
+    let content = oneio::read_to_string_lossy_async("tests/test_data_multi.txt.gz")
+        .await
+        .unwrap();
+    assert_eq!(content, TEST_TEXT);
+}
+
+#[cfg(feature = "bz")]
+#[tokio::test]
+async fn async_read_local_bzip2_multi_stream() {
+    let content = oneio::read_to_string_lossy_async("tests/test_data_multi.txt.bz2")
+        .await
+        .unwrap();
+    assert_eq!(content, TEST_TEXT);
+}
+
+#[test]
+fn async_multi_member_decoding_setting() {
+    let fixtures = [
+        #[cfg(feature = "any_gz")]
+        "tests/test_data_multi.txt.gz",
+        #[cfg(feature = "bz")]
+        "tests/test_data_multi.txt.bz2",
+    ];
+    // The client owns a blocking HTTP client, so it is built and dropped
+    // outside the runtime.
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    for (enabled, expected) in [(true, TEST_TEXT), (false, "OneIO test file.\n")] {
+        let oneio = oneio::OneIo::builder()
+            .multi_member_decoding(enabled)
+            .build()
+            .unwrap();
+        for fixture in fixtures {
+            let content = runtime.block_on(async {
+                let mut reader = oneio.get_reader_async(fixture).await.unwrap();
+                let mut content = String::new();
+                reader.read_to_string(&mut content).await.unwrap();
+                content
+            });
+            assert_eq!(content, expected, "{fixture}");
+        }
+    }
+}
+
 #[cfg(feature = "http")]
 #[tokio::test]
 async fn async_read_http_plain() {
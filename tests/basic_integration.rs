@@ -2,20 +2,32 @@
 //! These tests should always pass with `cargo test`
 
 use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
 
 const TEST_TEXT: &str = "OneIO test file.\nThis is a test.";
 
+/// Accepts `request_count` connections on a local port and answers each with
+/// `respond(request, stream)`, where `request` holds the request line and
+/// headers. Returns the server's base URL and a handle yielding the values
+/// returned by `respond`.
 #[cfg(feature = "http")]
-fn spawn_http_server(request_count: usize) -> (String, std::thread::JoinHandle<Vec<String>>) {
+fn spawn_http_server_with<T, F>(
+    request_count: usize,
+    mut respond: F,
+) -> (String, std::thread::JoinHandle<Vec<T>>)
+where
+    T: Send + 'static,
+    F: FnMut(&str, &mut std::net::TcpStream) -> T + Send + 'static,
+{
     use std::net::TcpListener;
     use std::time::Duration;
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
-    let body = TEST_TEXT.to_string();
 
     let handle = std::thread::spawn(move || {
-        let mut requests = Vec::with_capacity(request_count);
+        let mut results = Vec::with_capacity(request_count);
         for _ in 0..request_count {
             let (mut stream, _) = listener.accept().unwrap();
             stream
@@ -35,20 +47,30 @@ fn spawn_http_server(request_count: usize) -> (String, std::thread::JoinHandle<V
                 }
             }
 
-            requests.push(String::from_utf8(request).unwrap());
-
-            let response = format!(
-                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                body.len(),
-                body
-            );
-            stream.write_all(response.as_bytes()).unwrap();
-            stream.flush().unwrap();
+            let request = String::from_utf8(request).unwrap();
+            results.push(respond(&request, &mut stream));
         }
-        requests
+        results
+    });
+
+    (format!("http://{addr}"), handle)
+}
+
+#[cfg(feature = "http")]
+fn spawn_http_server(request_count: usize) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    let body = TEST_TEXT.to_string();
+    let (base, handle) = spawn_http_server_with(request_count, move |request, stream| {
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        stream.flush().unwrap();
+        request.to_string()
     });
 
-    (format!("http://{addr}/test.txt"), handle)
+    (format!("{base}/test.txt"), handle)
 }
 
 fn test_read(file_path: &str) {
@@ -288,6 +310,265 @@ fn test_get_reader_with_type_bz2_override() {
     assert_eq!(content.as_str(), TEST_TEXT);
 }
 
+// ── Magic-byte compression detection ──────────────────────────────────────────
+
+fn sniffing_oneio(detection: oneio::CompressionDetection) -> oneio::OneIo {
+    oneio::OneIo::builder()
+        .compression_detection(detection)
+        .build()
+        .unwrap()
+}
+
+/// Copies a fixture to a path with a different (or no) extension.
+fn copy_fixture(dir: &str, fixture: &str, name: &str) -> String {
+    std::fs::create_dir_all(dir).unwrap();
+    let path = format!("{dir}/{name}");
+    std::fs::copy(fixture, &path).unwrap();
+    path
+}
+
+#[test]
+fn test_sniff_detects_codec_without_extension() {
+    let dir = "tests/tmp_sniff_no_ext";
+    let fixtures: &[&str] = &[
+        #[cfg(feature = "any_gz")]
+        "tests/test_data.txt.gz",
+        #[cfg(feature = "bz")]
+        "tests/test_data.txt.bz2",
+        #[cfg(feature = "lz")]
+        "tests/test_data.txt.lz4",
+        #[cfg(feature = "xz")]
+        "tests/test_data.txt.xz",
+        #[cfg(feature = "zstd")]
+        "tests/test_data.txt.zst",
+    ];
+
+    std::fs::create_dir_all(dir).unwrap();
+    let oneio = sniffing_oneio(oneio::CompressionDetection::SniffOnly);
+    for (i, fixture) in fixtures.iter().enumerate() {
+        let path = copy_fixture(dir, fixture, &format!("rib.2024010{i}"));
+        let content = oneio.read_to_string_lossy(&path).unwrap();
+        assert_eq!(content, TEST_TEXT, "{fixture}");
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(feature = "any_gz", feature = "bz"))]
+#[test]
+fn test_sniff_overrides_misleading_extension() {
+    let dir = "tests/tmp_sniff_misleading";
+    let path = copy_fixture(dir, "tests/test_data.txt.gz", "data.bz2");
+
+    let oneio = sniffing_oneio(oneio::CompressionDetection::SniffWithExtensionFallback);
+    assert_eq!(oneio.read_to_string_lossy(&path).unwrap(), TEST_TEXT);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_sniff_passes_through_uncompressed_data() {
+    let oneio = sniffing_oneio(oneio::CompressionDetection::SniffOnly);
+    assert_eq!(
+        oneio.read_to_string_lossy("tests/test_data.txt").unwrap(),
+        TEST_TEXT
+    );
+
+    // Shorter than any magic number.
+    let dir = "tests/tmp_sniff_short";
+    std::fs::create_dir_all(dir).unwrap();
+    let path = format!("{dir}/short");
+    std::fs::write(&path, b"ab").unwrap();
+    assert_eq!(oneio.read_to_string_lossy(&path).unwrap(), "ab");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_sniff_only_ignores_extension() {
+    let dir = "tests/tmp_sniff_only";
+    let path = copy_fixture(dir, "tests/test_data.txt", "plain.gz");
+
+    let sniff_only = sniffing_oneio(oneio::CompressionDetection::SniffOnly);
+    assert_eq!(sniff_only.read_to_string_lossy(&path).unwrap(), TEST_TEXT);
+
+    // The fallback mode trusts the extension and fails to decode plain text.
+    let fallback = sniffing_oneio(oneio::CompressionDetection::SniffWithExtensionFallback);
+    assert!(fallback.read_to_string_lossy(&path).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_extension_only_is_default() {
+    let dir = "tests/tmp_sniff_default";
+    let path = copy_fixture(dir, "tests/test_data.txt.gz", "download");
+
+    let oneio = oneio::OneIo::new().unwrap();
+    let raw = oneio.read_to_bytes(&path).unwrap();
+    assert_eq!(raw, std::fs::read("tests/test_data.txt.gz").unwrap());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// ── Seekable reader ───────────────────────────────────────────────────────────
+
+/// Serves `body` honoring single `Range: bytes=a-b` / `bytes=a-` requests.
+#[cfg(feature = "http")]
+fn spawn_range_http_server(
+    body: Vec<u8>,
+    request_count: usize,
+) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    let (base, handle) = spawn_http_server_with(request_count, move |request, stream| {
+        let request = request.to_ascii_lowercase();
+        if request.starts_with("head ") {
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).unwrap();
+            return "HEAD".to_string();
+        }
+        let Some(range) = request
+            .lines()
+            .find_map(|line| line.strip_prefix("range: bytes="))
+            .map(|range| range.trim().to_string())
+        else {
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream
+                .write_all(header.as_bytes())
+                .and_then(|_| stream.write_all(&body));
+            return "GET".to_string();
+        };
+
+        let (start, end) = range.split_once('-').unwrap();
+        let start: usize = start.parse().unwrap();
+        let end: usize = end
+            .parse::<usize>()
+            .map_or(body.len() - 1, |end| end.min(body.len() - 1));
+        let header = format!(
+            "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {start}-{end}/{}\r\nConnection: close\r\n\r\n",
+            end + 1 - start,
+            body.len()
+        );
+        // The client may hang up once it has read enough of an open range.
+        let _ = stream
+            .write_all(header.as_bytes())
+            .and_then(|_| stream.write_all(&body[start..=end]));
+        range
+    });
+
+    (format!("{base}/data.bin"), handle)
+}
+
+#[test]
+fn test_seekable_reader_local() {
+    use std::io::{Seek, SeekFrom};
+
+    let mut reader = oneio::get_seekable_reader("tests/test_data.txt").unwrap();
+    assert_eq!(reader.content_length(), Some(TEST_TEXT.len() as u64));
+
+    reader.seek(SeekFrom::Start(17)).unwrap();
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(content, &TEST_TEXT[17..]);
+
+    reader.seek(SeekFrom::End(-5)).unwrap();
+    let mut tail = String::new();
+    reader.read_to_string(&mut tail).unwrap();
+    assert_eq!(tail, "test.");
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_seekable_reader_http_ranges() {
+    use std::io::{Seek, SeekFrom};
+
+    let body: Vec<u8> = (0..200_000u32).map(|i| (i % 256) as u8).collect();
+    let (url, handle) = spawn_range_http_server(body.clone(), 4);
+
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+    let mut reader = oneio.get_seekable_reader(&url).unwrap();
+
+    // Served from the probe's read-ahead buffer.
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header).unwrap();
+    assert_eq!(header, body[..8]);
+
+    // A footer near the end needs its own ranged request.
+    reader.seek(SeekFrom::Start(199_990)).unwrap();
+    let mut footer = Vec::new();
+    reader.read_to_end(&mut footer).unwrap();
+    assert_eq!(footer, body[199_990..]);
+
+    // Seeking back outside the buffer issues another open-ended range.
+    reader.seek(SeekFrom::Start(70_000)).unwrap();
+    let mut record = [0u8; 32];
+    reader.read_exact(&mut record).unwrap();
+    assert_eq!(record, body[70_000..70_032]);
+
+    drop(reader);
+    let ranges = handle.join().unwrap();
+    // The probe fills a whole block, so the length comes from a HEAD request.
+    assert_eq!(ranges, ["0-65535", "HEAD", "199990-", "70000-"]);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_seekable_reader_http_without_range_support() {
+    // The mock server ignores Range and always answers 200 OK.
+    let (url, handle) = spawn_http_server(1);
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+
+    let result = oneio.get_seekable_reader(&url);
+    assert!(matches!(
+        result,
+        Err(oneio::OneIoError::RangeNotSupported(_))
+    ));
+    handle.join().unwrap();
+}
+
+// ── read_range ────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_read_range_local() {
+    let bytes = oneio::read_range("tests/test_data.txt", 6, 4).unwrap();
+    assert_eq!(bytes, b"test");
+
+    // Truncated at end of file, empty past it.
+    let tail = oneio::read_range("tests/test_data.txt", 30, 100).unwrap();
+    assert_eq!(tail, &TEST_TEXT.as_bytes()[30..]);
+    assert!(oneio::read_range("tests/test_data.txt", 1_000, 10)
+        .unwrap()
+        .is_empty());
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_read_range_http() {
+    let body: Vec<u8> = (0..10_000u32).map(|i| (i % 256) as u8).collect();
+    let (url, handle) = spawn_range_http_server(body.clone(), 1);
+
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+    let bytes = oneio.read_range(&url, 4_096, 100).unwrap();
+    assert_eq!(bytes, body[4_096..4_196]);
+    assert_eq!(handle.join().unwrap(), ["4096-4195"]);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_read_range_http_ignored_range_is_error() {
+    let (url, handle) = spawn_http_server(1);
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+
+    let result = oneio.read_range(&url, 0, 4);
+    assert!(matches!(
+        result,
+        Err(oneio::OneIoError::RangeNotSupported(_))
+    ));
+    handle.join().unwrap();
+}
+
 // ── OneIoBuilder: timeout and configure_http ──────────────────────────────────
 
 #[cfg(feature = "http")]
@@ -490,6 +771,49 @@ fn test_get_reader_with_progress_local_no_total() {
     assert_eq!(*observed.lock().unwrap(), TEST_TEXT.len() as u64);
 }
 
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_progress_events_count_raw_and_decompressed_bytes() {
+    use oneio::{ProgressEvent, ProgressOptions};
+    use std::sync::{Arc, Mutex};
+
+    let oneio = oneio::OneIo::new().unwrap();
+    let raw_size = std::fs::metadata("tests/test_data.txt.gz").unwrap().len();
+    let read_events = |options: ProgressOptions| {
+        let events = Arc::new(Mutex::new(Vec::<ProgressEvent>::new()));
+        let events_cb = events.clone();
+        let mut reader = oneio
+            .get_reader_with_progress_events("tests/test_data.txt.gz", options, move |event| {
+                events_cb.lock().unwrap().push(*event);
+            })
+            .unwrap();
+        let mut buf = [0u8; 8];
+        while reader.read(&mut buf).unwrap() > 0 {}
+        // Reads after EOF do not repeat the final event.
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        let events = events.lock().unwrap().clone();
+        events
+    };
+
+    let events = read_events(ProgressOptions::every_read());
+    let last = events.last().unwrap();
+    assert!(last.done);
+    assert_eq!(last.raw_bytes, raw_size);
+    assert_eq!(last.decompressed_bytes, TEST_TEXT.len() as u64);
+    assert_eq!(last.total_bytes, Some(raw_size));
+    assert_eq!(last.fraction(), Some(1.0));
+    assert_eq!(last.resumes, 0);
+    assert_eq!(events.iter().filter(|event| event.done).count(), 1);
+    // 8-byte reads: one event per read, plus the final one.
+    assert_eq!(events.len(), TEST_TEXT.len().div_ceil(8) + 1);
+
+    // Throttled: only the first read and EOF are reported.
+    let events = read_events(ProgressOptions::new().interval(Duration::from_secs(3600)));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].decompressed_bytes, 8);
+    assert!(events[1].done);
+}
+
 // ── Phase 1: Cache reader ─────────────────────────────────────────────────────
 
 #[test]
@@ -593,6 +917,415 @@ fn test_cache_reader_creates_missing_cache_dir() {
     std::fs::remove_dir_all("tests/tmp_cache_dir_creation").unwrap();
 }
 
+/// Serves `body` with `ETag: etag`, answering `304` to matching
+/// `If-None-Match` requests. Returns the status of every response.
+#[cfg(feature = "http")]
+fn spawn_etag_http_server(
+    body: &'static str,
+    etag: &'static str,
+    request_count: usize,
+) -> (String, std::thread::JoinHandle<Vec<u16>>) {
+    let (base, handle) = spawn_http_server_with(request_count, move |request, stream| {
+        let request = request.to_ascii_lowercase();
+        let not_modified = request
+            .lines()
+            .filter_map(|line| line.strip_prefix("if-none-match: "))
+            .any(|value| value.trim() == etag.to_ascii_lowercase());
+        let response = if not_modified {
+            format!("HTTP/1.1 304 Not Modified\r\nETag: {etag}\r\nConnection: close\r\n\r\n")
+        } else {
+            format!(
+                "HTTP/1.1 200 OK\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        };
+        stream.write_all(response.as_bytes()).unwrap();
+        if not_modified {
+            304
+        } else {
+            200
+        }
+    });
+    (format!("{base}/data.txt"), handle)
+}
+
+/// Reads the `(cache file, source)` pairs of a cache directory's index.
+fn cache_index(cache_dir: &str) -> Vec<(String, String)> {
+    std::fs::read_to_string(format!("{cache_dir}/index.tsv"))
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(file, source)| (file.to_string(), source.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_cache_reader_keys_on_full_path() {
+    let dir = "tests/tmp_cache_keys";
+    let _ = std::fs::remove_dir_all(dir);
+    let cache_dir = format!("{dir}/cache");
+    let sources = [format!("{dir}/a/latest.txt"), format!("{dir}/b/latest.txt")];
+    for (source, content) in sources.iter().zip(["from a", "from b"]) {
+        std::fs::create_dir_all(Path::new(source).parent().unwrap()).unwrap();
+        std::fs::write(source, content).unwrap();
+    }
+
+    let oneio = oneio::OneIo::new().unwrap();
+    for _ in 0..2 {
+        for (source, expected) in sources.iter().zip(["from a", "from b"]) {
+            let mut content = String::new();
+            oneio
+                .get_cache_reader(source, &cache_dir, None, false)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            assert_eq!(content, expected);
+        }
+    }
+
+    let mut index = cache_index(&cache_dir);
+    index.sort_by(|a, b| a.1.cmp(&b.1));
+    let indexed: Vec<&str> = index.iter().map(|(_, source)| source.as_str()).collect();
+    assert_eq!(indexed, sources);
+    assert_ne!(index[0].0, index[1].0);
+    assert!(index.iter().all(|(file, _)| file.ends_with(".txt")));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_cache_reader_revalidates_with_etag() {
+    let cache_dir = "tests/tmp_cache_etag";
+    let _ = std::fs::remove_dir_all(cache_dir);
+    let (url, handle) = spawn_etag_http_server(TEST_TEXT, "\"v1\"", 2);
+
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+    for _ in 0..2 {
+        let mut content = String::new();
+        oneio
+            .get_cache_reader(&url, cache_dir, None, false)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, TEST_TEXT);
+    }
+    // The second read was answered from the cache after a 304.
+    assert_eq!(handle.join().unwrap(), [200, 304]);
+    let index = cache_index(cache_dir);
+    assert_eq!(index.len(), 1);
+    assert_eq!(index[0].1, url);
+    let meta = std::fs::read_to_string(format!("{cache_dir}/{}.meta", index[0].0)).unwrap();
+    assert!(meta.contains("etag: \"v1\""), "{meta}");
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_cache_reader_single_download_for_concurrent_callers() {
+    let cache_dir = "tests/tmp_cache_concurrent";
+    let _ = std::fs::remove_dir_all(cache_dir);
+    // Only one request is served; a second download would hang the test.
+    let (url, handle) = spawn_etag_http_server(TEST_TEXT, "\"v1\"", 1);
+
+    let oneio = oneio::OneIo::builder()
+        .no_proxy()
+        .cache_max_age(Duration::from_secs(3600))
+        .build()
+        .unwrap();
+    let barrier = std::sync::Barrier::new(8);
+    std::thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                barrier.wait();
+                let mut content = String::new();
+                oneio
+                    .get_cache_reader(&url, cache_dir, None, false)
+                    .unwrap()
+                    .read_to_string(&mut content)
+                    .unwrap();
+                assert_eq!(content, TEST_TEXT);
+            });
+        }
+    });
+    assert_eq!(handle.join().unwrap(), [200]);
+
+    // No temp files are left behind.
+    let mut names: Vec<String> = std::fs::read_dir(cache_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    let key = &cache_index(cache_dir)[0].0;
+    assert_eq!(
+        names,
+        [
+            key.clone(),
+            format!("{key}.lock"),
+            format!("{key}.meta"),
+            "index.tsv".to_string()
+        ]
+    );
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_cache_stream_through_commits_at_eof() {
+    let dir = "tests/tmp_cache_stream_through";
+    let _ = std::fs::remove_dir_all(dir);
+    let cache_dir = format!("{dir}/cache");
+    let source = "tests/test_data.txt.gz";
+    // Single-member decoding stops at the gzip trailer; the rest of the
+    // download must still reach the cache.
+    let oneio = oneio::OneIo::builder()
+        .cache_stream_through(true)
+        .multi_member_decoding(false)
+        .build()
+        .unwrap();
+    let cache_files = || -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(&cache_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| !name.ends_with(".lock"))
+            .collect();
+        names.sort();
+        names
+    };
+
+    // Dropped before EOF: the partial download is discarded.
+    let mut reader = oneio
+        .get_cache_reader(source, &cache_dir, None, false)
+        .unwrap();
+    let mut head = [0_u8; 5];
+    reader.read_exact(&mut head).unwrap();
+    assert_eq!(&head, b"OneIO");
+    assert!(cache_index(&cache_dir).is_empty());
+    drop(reader);
+    assert!(cache_files().is_empty(), "{:?}", cache_files());
+
+    // Read to EOF: the raw object is committed with its sidecar.
+    let mut content = String::new();
+    oneio
+        .get_cache_reader(source, &cache_dir, None, false)
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, TEST_TEXT);
+    let key = cache_index(&cache_dir)[0].0.clone();
+    assert_eq!(
+        cache_files(),
+        [key.clone(), format!("{key}.meta"), "index.tsv".to_string()]
+    );
+    assert_eq!(
+        std::fs::read(format!("{cache_dir}/{key}")).unwrap(),
+        std::fs::read(source).unwrap()
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_cache_stream_through_reopen_on_same_thread() {
+    let dir = "tests/tmp_cache_stream_reopen";
+    let _ = std::fs::remove_dir_all(dir);
+    let cache_dir = format!("{dir}/cache");
+    let source = "tests/test_data.txt.gz";
+    let oneio = oneio::OneIo::builder()
+        .cache_stream_through(true)
+        .build()
+        .unwrap();
+    let read_all = |reader: &mut dyn Read| {
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        content
+    };
+
+    // The first reader holds the entry while streaming it; opening the same
+    // entry again on this thread reads the source instead of waiting.
+    let mut first = oneio
+        .get_cache_reader(source, &cache_dir, None, false)
+        .unwrap();
+    let mut head = [0_u8; 5];
+    first.read_exact(&mut head).unwrap();
+    for stream_through in [true, false] {
+        let oneio = oneio::OneIo::builder()
+            .cache_stream_through(stream_through)
+            .build()
+            .unwrap();
+        let mut second = oneio
+            .get_cache_reader(source, &cache_dir, None, false)
+            .unwrap();
+        assert_eq!(read_all(&mut second), TEST_TEXT);
+    }
+    assert!(cache_index(&cache_dir).is_empty());
+
+    // The first reader still populates the cache.
+    assert_eq!(format!("OneIO{}", read_all(&mut first)), TEST_TEXT);
+    assert_eq!(cache_index(&cache_dir).len(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_cache_max_age_skips_revalidation() {
+    let cache_dir = "tests/tmp_cache_max_age";
+    let _ = std::fs::remove_dir_all(cache_dir);
+    // Only one request is served; a second one would hang the test.
+    let (url, handle) = spawn_etag_http_server(TEST_TEXT, "\"v1\"", 1);
+
+    let oneio = oneio::OneIo::builder()
+        .no_proxy()
+        .cache_max_age(std::time::Duration::from_secs(3600))
+        .build()
+        .unwrap();
+    for _ in 0..3 {
+        let mut content = String::new();
+        oneio
+            .get_cache_reader(&url, cache_dir, None, false)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, TEST_TEXT);
+    }
+    assert_eq!(handle.join().unwrap(), [200]);
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+#[test]
+fn test_cache_reader_refreshes_changed_local_source() {
+    let dir = "tests/tmp_cache_local_source";
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).unwrap();
+    let source = format!("{dir}/source.txt");
+    let cache_dir = format!("{dir}/cache");
+
+    let oneio = oneio::OneIo::new().unwrap();
+    std::fs::write(&source, "first").unwrap();
+    let read = || {
+        let mut content = String::new();
+        oneio
+            .get_cache_reader(&source, &cache_dir, None, false)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    };
+    assert_eq!(read(), "first");
+    std::fs::write(&source, "second version").unwrap();
+    assert_eq!(read(), "second version");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_managed_cache_evicts_least_recently_used() {
+    use oneio::{CacheStats, OneIoCache};
+
+    let dir = "tests/tmp_managed_cache_lru";
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(format!("{dir}/cache")).unwrap();
+    for name in ["a", "b", "c"] {
+        std::fs::write(format!("{dir}/{name}.txt"), name.repeat(1000)).unwrap();
+    }
+    std::fs::write(format!("{dir}/cache/unrelated.txt"), "keep").unwrap();
+
+    // Room for two 1000-byte entries with their sidecars, but not three.
+    let cache = OneIoCache::new(format!("{dir}/cache")).max_bytes(2800);
+    let oneio = oneio::OneIo::builder()
+        .cache(cache.clone())
+        .build()
+        .unwrap();
+    let read = |name: &str| {
+        let mut content = String::new();
+        oneio
+            .get_cached_reader(&format!("{dir}/{name}.txt"))
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    };
+
+    // Access times have one-second resolution.
+    read("a");
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    read("b");
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    assert_eq!(read("a"), "a".repeat(1000));
+    read("c");
+
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 1,
+            misses: 3,
+            evictions: 1
+        }
+    );
+    let mut cached: Vec<String> = cache_index(&format!("{dir}/cache"))
+        .into_iter()
+        .map(|(file, source)| {
+            assert!(Path::new(&format!("{dir}/cache/{file}")).exists());
+            source
+        })
+        .collect();
+    cached.sort();
+    assert_eq!(cached, [format!("{dir}/a.txt"), format!("{dir}/c.txt")]);
+    assert_eq!(cache.prune().unwrap(), 0);
+
+    cache.clear().unwrap();
+    let (locks, mut remaining): (Vec<String>, Vec<String>) =
+        std::fs::read_dir(format!("{dir}/cache"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .partition(|name| name.ends_with(".lock"));
+    remaining.sort();
+    assert_eq!(remaining, ["unrelated.txt"]);
+    // Lock files outlive their entries so concurrent populators keep
+    // contending on the same file.
+    assert!(!locks.is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_managed_cache_ttl_expires_entries() {
+    let dir = "tests/tmp_managed_cache_ttl";
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).unwrap();
+    let source = format!("{dir}/source.txt");
+    std::fs::write(&source, "content").unwrap();
+
+    let cache = oneio::OneIoCache::new(format!("{dir}/cache")).ttl(Duration::ZERO);
+    let oneio = oneio::OneIo::builder()
+        .cache(cache.clone())
+        .build()
+        .unwrap();
+    for _ in 0..2 {
+        let mut content = String::new();
+        oneio
+            .get_cached_reader(&source)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "content");
+    }
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.evictions), (0, 2, 1));
+    assert_eq!(cache.prune().unwrap(), 1);
+    assert!(cache_index(&format!("{dir}/cache")).is_empty());
+    // Only the index and the entry's lock file are left.
+    assert_eq!(
+        std::fs::read_dir(format!("{dir}/cache")).unwrap().count(),
+        2
+    );
+
+    let unconfigured = oneio::OneIo::new().unwrap();
+    assert!(matches!(
+        unconfigured.get_cached_reader(&source),
+        Err(oneio::OneIoError::NotSupported(_))
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
 // ── Phase 1: JSON parsing ─────────────────────────────────────────────────────
 
 #[cfg(feature = "json")]
@@ -699,54 +1432,266 @@ fn test_get_sha256_digest_missing_file_returns_error() {
     assert!(result.is_err());
 }
 
-// ── Phase 2: Error variants ───────────────────────────────────────────────────
-
-// Note: reqwest::Certificate::from_pem/from_der do not validate certificate data
-// at parse time. They only validate when used in a TLS connection. Therefore,
-// we cannot test for InvalidCertificate errors with invalid data here.
-
-#[cfg(feature = "http")]
+#[cfg(all(feature = "digest", feature = "any_gz"))]
 #[test]
-fn test_network_error_on_refused_connection() {
-    // Port 1 is reserved and always refuses connections — produces a network error.
-    let oneio = oneio::OneIo::new().unwrap();
-    let result = oneio.get_reader("http://127.0.0.1:1/file.txt");
-    assert!(result.is_err());
-    // Error display must be non-empty and useful.
-    assert!(!result.err().unwrap().to_string().is_empty());
-}
+fn test_get_reader_with_digest() {
+    use oneio::{Algorithm, DigestMode};
 
-// ── Phase 2: Environment variables ───────────────────────────────────────────
+    let oneio = oneio::OneIo::new().unwrap();
+    let (mut reader, digests) = oneio
+        .get_reader_with_digest(
+            "tests/test_data.txt.gz",
+            &[Algorithm::Md5],
+            &[Algorithm::Sha256, Algorithm::Blake3],
+        )
+        .unwrap();
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(
+        content,
+        std::fs::read_to_string("tests/test_data.txt").unwrap()
+    );
 
-#[cfg(all(feature = "http", any(feature = "rustls", feature = "native-tls")))]
-#[test]
-fn test_oneio_ca_bundle_env_var_valid_path() {
-    // Point ONEIO_CA_BUNDLE at a known PEM cert — builder must succeed.
-    std::env::set_var("ONEIO_CA_BUNDLE", "tests/test-cert.pem");
-    let result = oneio::OneIo::builder().build();
-    std::env::remove_var("ONEIO_CA_BUNDLE");
-    assert!(
-        result.is_ok(),
-        "builder failed with valid ONEIO_CA_BUNDLE: {:?}",
-        result.err()
+    let raw_md5 = oneio::get_digest("tests/test_data.txt.gz", Algorithm::Md5, DigestMode::Raw);
+    assert_eq!(digests.raw.digests().unwrap(), [raw_md5.unwrap()]);
+    assert_eq!(
+        digests.decompressed.digest(Algorithm::Sha256),
+        Some("51a6f9bf51d9e6243fe838242bb74e6e16f77c87cae138b9f3e065c173fc63c7")
+    );
+    let blake3 = oneio::get_digest("tests/test_data.txt", Algorithm::Blake3, DigestMode::Raw);
+    assert_eq!(
+        digests.decompressed.digest(Algorithm::Blake3),
+        Some(blake3.unwrap().as_str())
     );
 }
 
-#[cfg(all(feature = "http", any(feature = "rustls", feature = "native-tls")))]
+#[cfg(all(feature = "digest", feature = "any_gz"))]
 #[test]
-fn test_oneio_ca_bundle_env_var_missing_path() {
-    // A non-existent path must be silently ignored (not panic or error).
-    std::env::set_var("ONEIO_CA_BUNDLE", "/tmp/oneio_does_not_exist_ca.pem");
-    let result = oneio::OneIo::builder().build();
-    std::env::remove_var("ONEIO_CA_BUNDLE");
-    assert!(
-        result.is_ok(),
-        "builder should ignore missing ONEIO_CA_BUNDLE"
-    );
+fn test_get_digests_raw_and_decompressed() {
+    use oneio::{Algorithm, DigestMode};
+
+    const EXPECTED: &str = "51a6f9bf51d9e6243fe838242bb74e6e16f77c87cae138b9f3e065c173fc63c7";
+    let decompressed = oneio::get_digest(
+        "tests/test_data.txt.gz",
+        Algorithm::Sha256,
+        DigestMode::Decompressed,
+    )
+    .unwrap();
+    assert_eq!(decompressed, EXPECTED);
+    let raw =
+        oneio::get_digest("tests/test_data.txt.gz", Algorithm::Sha256, DigestMode::Raw).unwrap();
+    assert_ne!(raw, EXPECTED);
+
+    let algorithms = [Algorithm::Sha256, Algorithm::Md5, Algorithm::Crc32c];
+    let digests = oneio::get_digests("tests/test_data.txt", &algorithms, DigestMode::Raw).unwrap();
+    assert_eq!(digests[0], EXPECTED);
+    for (algorithm, digest) in algorithms.iter().zip(&digests) {
+        let single = oneio::get_digest("tests/test_data.txt", *algorithm, DigestMode::Raw);
+        assert_eq!(&single.unwrap(), digest);
+    }
 }
 
-#[cfg(all(feature = "http", any(feature = "rustls", feature = "native-tls")))]
-#[test]
+/// Backend serving a fixed set of objects under the `sums://` scheme.
+#[cfg(feature = "digest")]
+struct ChecksumBackend(Vec<(&'static str, String)>);
+
+#[cfg(feature = "digest")]
+impl oneio::Backend for ChecksumBackend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, oneio::OneIoError> {
+        let (_, body) = self
+            .0
+            .iter()
+            .find(|(name, _)| *name == path.trim_start_matches("sums://"))
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, path.to_string()))?;
+        Ok(Box::new(std::io::Cursor::new(body.clone().into_bytes())))
+    }
+}
+
+#[cfg(feature = "digest")]
+const HELLO_SHA256: &str = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+#[cfg(feature = "digest")]
+const HELLO_MD5: &str = "b1946ac92492d2347c6235b4d2611184";
+
+#[cfg(feature = "digest")]
+#[test]
+fn test_download_verified_explicit_checksums() {
+    use oneio::Checksum;
+
+    let dir = "tests/tmp_download_verified";
+    let _ = std::fs::remove_dir_all(dir);
+    let local = format!("{dir}/hello.txt");
+    let oneio = oneio::OneIo::builder()
+        .backend(
+            "sums",
+            ChecksumBackend(vec![("data/hello.txt", "hello\n".to_string())]),
+        )
+        .build()
+        .unwrap();
+
+    let remote = "sums://data/hello.txt";
+    oneio
+        .download_verified(
+            remote,
+            &local,
+            Checksum::Sha256(HELLO_SHA256.to_uppercase()),
+        )
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&local).unwrap(), "hello\n");
+    oneio
+        .download_verified(remote, &local, Checksum::Md5(HELLO_MD5.to_string()))
+        .unwrap();
+
+    let wrong = "0".repeat(64);
+    match oneio.download_verified(remote, &local, Checksum::Sha256(wrong.clone())) {
+        Err(oneio::OneIoError::ChecksumMismatch {
+            algorithm,
+            expected,
+            actual,
+            ..
+        }) => {
+            assert_eq!(
+                (algorithm, expected, actual),
+                ("sha256", wrong, HELLO_SHA256.to_string())
+            );
+        }
+        other => panic!("expected a checksum mismatch, got {other:?}"),
+    }
+    // Failed downloads never replace the previous file.
+    std::fs::write(&local, "previous").unwrap();
+    assert!(oneio
+        .download_verified(remote, &local, Checksum::Md5("0".repeat(32)))
+        .is_err());
+    assert_eq!(std::fs::read_to_string(&local).unwrap(), "previous");
+    assert!(oneio
+        .download_verified(remote, &local, Checksum::Md5("not hex".to_string()))
+        .is_err());
+
+    /// Yields a few bytes, then fails like a reset connection.
+    struct ResetBackend;
+    impl oneio::Backend for ResetBackend {
+        fn reader_raw(&self, _path: &str) -> Result<Box<dyn Read + Send>, oneio::OneIoError> {
+            struct Reset;
+            impl Read for Reset {
+                fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                    Err(std::io::ErrorKind::ConnectionReset.into())
+                }
+            }
+            Ok(Box::new(std::io::Cursor::new(b"hel".to_vec()).chain(Reset)))
+        }
+    }
+    let resetting = oneio::OneIo::builder()
+        .backend("reset", ResetBackend)
+        .build()
+        .unwrap();
+    let partial = format!("{dir}/partial.txt");
+    assert!(resetting
+        .download_verified(
+            "reset://data/hello.txt",
+            &partial,
+            Checksum::Sha256(HELLO_SHA256.to_string())
+        )
+        .is_err());
+    assert!(!Path::new(&partial).exists());
+    assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn test_download_verified_discovers_checksum_files() {
+    use oneio::Checksum;
+
+    let dir = "tests/tmp_download_verified_auto";
+    let _ = std::fs::remove_dir_all(dir);
+    let local = format!("{dir}/hello.txt");
+    let sha256_file = format!("{HELLO_SHA256}  a.txt\n");
+    let files = vec![
+        ("a/a.txt", "hello\n".to_string()),
+        ("a/a.txt.sha256", sha256_file.clone()),
+        ("a/a.txt.md5", "0".repeat(32)),
+        ("b/b.txt", "hello\n".to_string()),
+        ("b/b.txt.md5", format!("{HELLO_MD5}\n")),
+        ("c/c.txt", "hello\n".to_string()),
+        (
+            "c/SHA256SUMS",
+            format!("{}  other.txt\n{HELLO_SHA256} *c.txt\n", "0".repeat(64)),
+        ),
+        ("d/d.txt", "tampered\n".to_string()),
+        ("d/d.txt.sha256", sha256_file),
+        ("e/e.txt", "hello\n".to_string()),
+    ];
+    let oneio = oneio::OneIo::builder()
+        .backend("sums", ChecksumBackend(files))
+        .build()
+        .unwrap();
+
+    // `.sha256` wins over `.md5`, which wins over `SHA256SUMS`.
+    for remote in ["sums://a/a.txt", "sums://b/b.txt", "sums://c/c.txt"] {
+        oneio
+            .download_verified(remote, &local, Checksum::Auto)
+            .unwrap_or_else(|e| panic!("{remote}: {e}"));
+        assert_eq!(std::fs::read_to_string(&local).unwrap(), "hello\n");
+    }
+    assert!(matches!(
+        oneio.download_verified("sums://d/d.txt", &local, Checksum::Auto),
+        Err(oneio::OneIoError::ChecksumMismatch { .. })
+    ));
+    assert_eq!(std::fs::read_to_string(&local).unwrap(), "hello\n");
+    match oneio.download_verified("sums://e/e.txt", &local, Checksum::Auto) {
+        Err(oneio::OneIoError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+        other => panic!("expected a missing checksum error, got {other:?}"),
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+// ── Phase 2: Error variants ───────────────────────────────────────────────────
+
+// Note: reqwest::Certificate::from_pem/from_der do not validate certificate data
+// at parse time. They only validate when used in a TLS connection. Therefore,
+// we cannot test for InvalidCertificate errors with invalid data here.
+
+#[cfg(feature = "http")]
+#[test]
+fn test_network_error_on_refused_connection() {
+    // Port 1 is reserved and always refuses connections — produces a network error.
+    let oneio = oneio::OneIo::new().unwrap();
+    let result = oneio.get_reader("http://127.0.0.1:1/file.txt");
+    assert!(result.is_err());
+    // Error display must be non-empty and useful.
+    assert!(!result.err().unwrap().to_string().is_empty());
+}
+
+// ── Phase 2: Environment variables ───────────────────────────────────────────
+
+#[cfg(all(feature = "http", any(feature = "rustls", feature = "native-tls")))]
+#[test]
+fn test_oneio_ca_bundle_env_var_valid_path() {
+    // Point ONEIO_CA_BUNDLE at a known PEM cert — builder must succeed.
+    std::env::set_var("ONEIO_CA_BUNDLE", "tests/test-cert.pem");
+    let result = oneio::OneIo::builder().build();
+    std::env::remove_var("ONEIO_CA_BUNDLE");
+    assert!(
+        result.is_ok(),
+        "builder failed with valid ONEIO_CA_BUNDLE: {:?}",
+        result.err()
+    );
+}
+
+#[cfg(all(feature = "http", any(feature = "rustls", feature = "native-tls")))]
+#[test]
+fn test_oneio_ca_bundle_env_var_missing_path() {
+    // A non-existent path must be silently ignored (not panic or error).
+    std::env::set_var("ONEIO_CA_BUNDLE", "/tmp/oneio_does_not_exist_ca.pem");
+    let result = oneio::OneIo::builder().build();
+    std::env::remove_var("ONEIO_CA_BUNDLE");
+    assert!(
+        result.is_ok(),
+        "builder should ignore missing ONEIO_CA_BUNDLE"
+    );
+}
+
+#[cfg(all(feature = "http", any(feature = "rustls", feature = "native-tls")))]
+#[test]
 fn test_oneio_accept_invalid_certs_env_var() {
     // Builder must succeed when env var is set to "true".
     std::env::set_var("ONEIO_ACCEPT_INVALID_CERTS", "true");
@@ -754,3 +1699,1291 @@ fn test_oneio_accept_invalid_certs_env_var() {
     std::env::remove_var("ONEIO_ACCEPT_INVALID_CERTS");
     assert!(result.is_ok());
 }
+
+// ── Pluggable backends ───────────────────────────────────────────────────────
+
+/// In-memory backend serving fixture files under a custom `mem://` scheme.
+struct MemBackend;
+
+impl oneio::Backend for MemBackend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, oneio::OneIoError> {
+        let name = path.trim_start_matches("mem://");
+        let bytes = std::fs::read(format!("tests/{name}"))?;
+        Ok(Box::new(std::io::Cursor::new(bytes)))
+    }
+
+    fn stat(&self, path: &str) -> Result<oneio::ObjectStat, oneio::OneIoError> {
+        let name = path.trim_start_matches("mem://");
+        Ok(oneio::ObjectStat {
+            content_length: Some(std::fs::metadata(format!("tests/{name}"))?.len()),
+            ..Default::default()
+        })
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, oneio::OneIoError> {
+        let name = path.trim_start_matches("mem://");
+        Ok(std::path::Path::new(&format!("tests/{name}")).exists())
+    }
+}
+
+#[test]
+fn test_custom_backend_gets_decompression_and_progress() {
+    let oneio = oneio::OneIo::builder()
+        .backend("mem", MemBackend)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        oneio.read_to_string_lossy("mem://test_data.txt").unwrap(),
+        TEST_TEXT
+    );
+    #[cfg(feature = "any_gz")]
+    assert_eq!(
+        oneio
+            .read_to_string_lossy("mem://test_data.txt.gz")
+            .unwrap(),
+        TEST_TEXT
+    );
+
+    assert!(oneio.exists("mem://test_data.txt").unwrap());
+    assert!(!oneio.exists("mem://missing.txt").unwrap());
+    assert_eq!(
+        oneio.get_content_length("mem://test_data.txt").unwrap(),
+        TEST_TEXT.len() as u64
+    );
+
+    let (mut reader, total) = oneio
+        .get_reader_with_progress("mem://test_data.txt", |_, _| {})
+        .unwrap();
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(content, TEST_TEXT);
+    assert_eq!(total, Some(TEST_TEXT.len() as u64));
+}
+
+#[test]
+fn test_custom_backend_download_and_cache() {
+    let oneio = oneio::OneIo::builder()
+        .backend("mem", MemBackend)
+        .build()
+        .unwrap();
+
+    let out = "tests/tmp_backend_download.txt";
+    oneio.download("mem://test_data.txt", out).unwrap();
+    assert_eq!(std::fs::read_to_string(out).unwrap(), TEST_TEXT);
+    std::fs::remove_file(out).unwrap();
+
+    let cache_dir = "tests/tmp_backend_cache";
+    let _ = std::fs::remove_dir_all(cache_dir);
+    let content = oneio
+        .to_lines_lossy(
+            oneio
+                .get_cache_reader("mem://test_data.txt", cache_dir, None, false)
+                .unwrap(),
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(content.len(), 2);
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+/// Backend keeping objects in a shared map, committed when writers finish.
+#[derive(Clone, Default)]
+struct MapBackend {
+    objects: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>>,
+}
+
+impl oneio::Backend for MapBackend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, oneio::OneIoError> {
+        self.read_range(path, 0, None)
+    }
+
+    fn read_range(
+        &self,
+        path: &str,
+        offset: u64,
+        len: Option<u64>,
+    ) -> Result<Box<dyn Read + Send>, oneio::OneIoError> {
+        let objects = self.objects.lock().unwrap();
+        let bytes = objects
+            .get(path)
+            .ok_or_else(|| oneio::OneIoError::NotSupported(path.to_string()))?;
+        let start = (offset as usize).min(bytes.len());
+        let end = len.map_or(bytes.len(), |len| (start + len as usize).min(bytes.len()));
+        Ok(Box::new(std::io::Cursor::new(bytes[start..end].to_vec())))
+    }
+
+    fn writer_raw(&self, path: &str) -> Result<oneio::OneIoWriter, oneio::OneIoError> {
+        let objects = self.objects.clone();
+        let path = path.to_string();
+        Ok(oneio::OneIoWriter::with_finish(Vec::new(), move |bytes| {
+            objects.lock().unwrap().insert(path, bytes);
+            Ok(())
+        }))
+    }
+}
+
+#[test]
+fn test_custom_backend_writer_and_seek() {
+    use std::io::Seek;
+
+    let backend = MapBackend::default();
+    let oneio = oneio::OneIo::builder()
+        .backend("map", backend.clone())
+        .backend("file", backend.clone())
+        .build()
+        .unwrap();
+
+    for path in ["map://out.txt", "tests/tmp_map_override.txt"] {
+        let mut writer = oneio.get_writer(path).unwrap();
+        writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+        assert!(backend.objects.lock().unwrap().get(path).is_none());
+        writer.finish().unwrap();
+        assert_eq!(oneio.read_to_string_lossy(path).unwrap(), TEST_TEXT);
+
+        let mut reader = oneio.get_seekable_reader(path).unwrap();
+        reader.seek(std::io::SeekFrom::Start(5)).unwrap();
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, &TEST_TEXT[5..]);
+    }
+    // The overriding `file` backend handled the local path.
+    assert!(!Path::new("tests/tmp_map_override.txt").exists());
+
+    #[cfg(feature = "any_gz")]
+    {
+        let mut writer = oneio.get_writer("map://out.txt.gz").unwrap();
+        writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(
+            oneio.read_to_string_lossy("map://out.txt.gz").unwrap(),
+            TEST_TEXT
+        );
+    }
+}
+
+#[test]
+fn test_unregistered_scheme_is_not_supported() {
+    let oneio = oneio::OneIo::new().unwrap();
+    assert!(matches!(
+        oneio.get_reader("nosuch://host/file.txt"),
+        Err(oneio::OneIoError::NotSupported(_))
+    ));
+}
+
+#[test]
+fn test_local_backend_lists_directory() {
+    let oneio = oneio::OneIo::new().unwrap();
+    let entries = oneio.backend("tests").unwrap().list("tests").unwrap();
+    let entry = entries
+        .iter()
+        .find(|entry| entry.name == "test_data.txt")
+        .unwrap();
+    assert_eq!(entry.size, Some(TEST_TEXT.len() as u64));
+    assert!(!entry.is_dir);
+}
+
+// ── Directory listing ────────────────────────────────────────────────────────
+
+#[test]
+fn test_list_local_directory() {
+    let dir = "tests/tmp_list_local";
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(format!("{dir}/sub")).unwrap();
+    std::fs::write(format!("{dir}/a.txt"), TEST_TEXT).unwrap();
+
+    let entries = oneio::list(dir).unwrap();
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["a.txt", "sub"]);
+    assert_eq!(entries[0].size, Some(TEST_TEXT.len() as u64));
+    assert_eq!(entries[0].path, format!("{dir}/a.txt"));
+    let modified = entries[0].last_modified.as_deref().unwrap();
+    assert!(
+        modified.len() == 20 && modified.ends_with('Z'),
+        "{modified}"
+    );
+    assert!(entries[1].is_dir);
+    assert_eq!(entries[1].size, None);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_list_http_autoindex() {
+    let html = r#"<html><head><title>Index of /data/</title></head>
+<body><h1>Index of /data/</h1><hr><pre><a href="../">../</a>
+<a href="RIBS/">RIBS/</a>                                              05-Jan-2024 10:01                   -
+<a href="updates.20240105.1000.gz">updates.20240105.1000.gz</a>           05-Jan-2024 10:05               12345
+</pre><hr></body></html>"#;
+    let (url, handle) = spawn_range_http_server(html.as_bytes().to_vec(), 1);
+
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+    let entries = oneio.list(&url).unwrap();
+    handle.join().unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "RIBS");
+    assert!(entries[0].is_dir);
+    assert_eq!(entries[1].name, "updates.20240105.1000.gz");
+    assert_eq!(entries[1].size, Some(12345));
+    assert_eq!(
+        entries[1].last_modified.as_deref(),
+        Some("2024-01-05T10:05:00Z")
+    );
+    // Entry paths resolve against the listed URL, treated as a directory.
+    assert_eq!(entries[1].path, format!("{url}/updates.20240105.1000.gz"));
+}
+
+// ── Glob patterns ────────────────────────────────────────────────────────────
+
+/// Builds `{dir}/2024/{01,02}/updates.*` files plus a nested decoy.
+fn build_glob_fixture(dir: &str) {
+    let _ = std::fs::remove_dir_all(dir);
+    for (path, content) in [
+        ("2024/01/updates.0000.txt", "jan 1\njan 2\n"),
+        ("2024/01/updates.0015.txt", "jan 3\n"),
+        ("2024/02/updates.0000.txt", "feb 1"),
+        ("2024/02/ribs/rib.0000.txt", "rib"),
+        ("2023/12/updates.0000.txt", "dec"),
+    ] {
+        let path = format!("{dir}/{path}");
+        std::fs::create_dir_all(std::path::Path::new(&path).parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+}
+
+#[test]
+fn test_glob_local_patterns() {
+    let dir = "tests/tmp_glob";
+    build_glob_fixture(dir);
+
+    assert_eq!(
+        oneio::glob(&format!("{dir}/2024/*/updates.*.txt")).unwrap(),
+        [
+            format!("{dir}/2024/01/updates.0000.txt"),
+            format!("{dir}/2024/01/updates.0015.txt"),
+            format!("{dir}/2024/02/updates.0000.txt"),
+        ]
+    );
+    assert_eq!(
+        oneio::glob(&format!("{dir}/**/*.0000.txt")).unwrap().len(),
+        4
+    );
+    assert_eq!(
+        oneio::glob(&format!("{dir}/{{2023/12,2024/02}}/updates.00?0.txt")).unwrap(),
+        [
+            format!("{dir}/2023/12/updates.0000.txt"),
+            format!("{dir}/2024/02/updates.0000.txt"),
+        ]
+    );
+    assert!(oneio::glob(&format!("{dir}/2025/*")).unwrap().is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Serves local files under the `dir://` scheme through a custom backend.
+struct DirBackend;
+
+impl oneio::Backend for DirBackend {
+    fn reader_raw(&self, path: &str) -> Result<Box<dyn Read + Send>, oneio::OneIoError> {
+        Ok(Box::new(std::fs::File::open(
+            path.trim_start_matches("dir://"),
+        )?))
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<oneio::ListEntry>, oneio::OneIoError> {
+        oneio::list(path.trim_start_matches("dir://"))
+    }
+}
+
+#[test]
+fn test_glob_lists_through_registered_backends() {
+    let dir = "tests/tmp_glob_backend";
+    build_glob_fixture(dir);
+    let oneio = oneio::OneIo::builder()
+        .backend("dir", DirBackend)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        oneio
+            .glob(&format!("dir://{dir}/2024/*/updates.*.txt"))
+            .unwrap(),
+        [
+            format!("dir://{dir}/2024/01/updates.0000.txt"),
+            format!("dir://{dir}/2024/01/updates.0015.txt"),
+            format!("dir://{dir}/2024/02/updates.0000.txt"),
+        ]
+    );
+    assert_eq!(
+        oneio.glob(&format!("dir://{dir}/**/rib.*.txt")).unwrap(),
+        [format!("dir://{dir}/2024/02/ribs/rib.0000.txt")]
+    );
+    assert_eq!(
+        oneio
+            .glob(&format!("dir://{dir}/2023/12/updates.0000.txt"))
+            .unwrap(),
+        [format!("dir://{dir}/2023/12/updates.0000.txt")]
+    );
+    assert!(oneio
+        .glob(&format!("dir://{dir}/2025/*/updates.*"))
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        oneio.glob("none://host/*.txt"),
+        Err(oneio::OneIoError::NotSupported(_))
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_read_lines_lossy_glob_streams_files_in_order() {
+    let dir = "tests/tmp_glob_lines";
+    build_glob_fixture(dir);
+
+    let lines: Vec<String> = oneio::read_lines_lossy_glob(&format!("{dir}/2024/0?/updates.*"))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(lines, ["jan 1", "jan 2", "jan 3", "feb 1"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// ── Download and writer progress ─────────────────────────────────────────────
+
+#[test]
+fn test_download_with_progress() {
+    let oneio = oneio::OneIo::builder()
+        .backend("mem", MemBackend)
+        .build()
+        .unwrap();
+
+    let out = "tests/tmp_download_progress.txt";
+    let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let reports_cb = reports.clone();
+    let total = oneio
+        .download_with_progress("mem://test_data.txt", out, move |bytes, total| {
+            reports_cb.lock().unwrap().push((bytes, total));
+        })
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(out).unwrap(), TEST_TEXT);
+    std::fs::remove_file(out).unwrap();
+
+    let len = TEST_TEXT.len() as u64;
+    assert_eq!(total, Some(len));
+    assert_eq!(reports.lock().unwrap().last(), Some(&(len, len)));
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_writer_with_progress_reports_compressed_bytes() {
+    let oneio = oneio::OneIo::new().unwrap();
+    let out = "tests/tmp_writer_progress.txt.gz";
+    let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let reports_cb = reports.clone();
+    let mut writer = oneio
+        .get_writer_with_progress(out, move |written, compressed| {
+            reports_cb.lock().unwrap().push((written, compressed));
+        })
+        .unwrap();
+    for _ in 0..100 {
+        writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let reports = reports.lock().unwrap();
+    let written = 100 * TEST_TEXT.len() as u64;
+    let compressed = std::fs::metadata(out).unwrap().len();
+    assert_eq!(reports.last(), Some(&(written, compressed)));
+    assert!(reports
+        .windows(2)
+        .all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+    assert_eq!(
+        oneio.read_to_string_lossy(out).unwrap(),
+        TEST_TEXT.repeat(100)
+    );
+    std::fs::remove_file(out).unwrap();
+}
+
+#[test]
+fn test_writer_progress_events_are_throttled() {
+    let oneio = oneio::OneIo::new().unwrap();
+    let out = "tests/tmp_writer_progress_events.txt";
+    let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_cb = events.clone();
+    let options = oneio::ProgressOptions::new().interval(Duration::from_secs(3600));
+    let mut writer = oneio
+        .get_writer_with_progress_events(
+            out,
+            "",
+            &oneio::WriterOptions::default(),
+            options,
+            move |event| {
+                events_cb.lock().unwrap().push(*event);
+            },
+        )
+        .unwrap();
+    for _ in 0..100 {
+        writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+
+    // One event for the first write and a final one from `finish`.
+    let events = events.lock().unwrap();
+    let written = 100 * TEST_TEXT.len() as u64;
+    assert_eq!(events.len(), 2);
+    assert!(!events[0].done);
+    assert!(events[1].done);
+    assert_eq!(events[1].decompressed_bytes, written);
+    assert_eq!(events[1].raw_bytes, written);
+    assert_eq!(events[1].total_bytes, None);
+    std::fs::remove_file(out).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_writer_progress_events_with_type_and_options() {
+    let oneio = oneio::OneIo::new().unwrap();
+    let out = "tests/tmp_writer_progress_events_gz.txt";
+    let last = std::sync::Arc::new(std::sync::Mutex::new(None));
+    let last_cb = last.clone();
+    let mut writer = oneio
+        .get_writer_with_progress_events(
+            out,
+            "gz",
+            &oneio::WriterOptions::new().level(9),
+            oneio::ProgressOptions::default(),
+            move |event| {
+                *last_cb.lock().unwrap() = Some(*event);
+            },
+        )
+        .unwrap();
+    for _ in 0..100 {
+        writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let last = last.lock().unwrap().unwrap();
+    let written = 100 * TEST_TEXT.len() as u64;
+    assert!(last.done);
+    assert_eq!(last.decompressed_bytes, written);
+    assert_eq!(last.raw_bytes, std::fs::metadata(out).unwrap().len());
+    assert!(last.raw_bytes < written);
+    let mut content = String::new();
+    oneio
+        .get_reader_with_type(out, "gz")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, TEST_TEXT.repeat(100));
+    std::fs::remove_file(out).unwrap();
+}
+
+// ── Bandwidth limiting ───────────────────────────────────────────────────────
+
+#[test]
+fn test_rate_limit_is_shared_across_clones() {
+    let oneio = oneio::OneIo::builder()
+        .backend("mem", MemBackend)
+        .rate_limit(TEST_TEXT.len() as u64)
+        .build()
+        .unwrap();
+
+    // The bucket holds one second of budget: two concurrent reads of the
+    // same size through clones must wait about one second for the second.
+    let started = std::time::Instant::now();
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let oneio = oneio.clone();
+            std::thread::spawn(move || oneio.read_to_string_lossy("mem://test_data.txt").unwrap())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), TEST_TEXT);
+    }
+    assert!(started.elapsed() >= Duration::from_millis(900));
+
+    // Local paths are not limited.
+    let started = std::time::Instant::now();
+    for _ in 0..3 {
+        assert_eq!(
+            oneio.read_to_string_lossy("tests/test_data.txt").unwrap(),
+            TEST_TEXT
+        );
+    }
+    assert!(started.elapsed() < Duration::from_millis(900));
+}
+
+#[test]
+fn test_rate_limit_per_host_spans_sequential_transfers() {
+    let oneio = oneio::OneIo::builder()
+        .backend("mem", MemBackend)
+        .rate_limit_per_host(TEST_TEXT.len() as u64)
+        .build()
+        .unwrap();
+
+    // The first read drains the host's one-second budget; the host's bucket
+    // outlives it, so the second read has to wait about a second.
+    let started = std::time::Instant::now();
+    for _ in 0..2 {
+        assert_eq!(
+            oneio.read_to_string_lossy("mem://test_data.txt").unwrap(),
+            TEST_TEXT
+        );
+    }
+    assert!(started.elapsed() >= Duration::from_millis(900));
+}
+
+#[test]
+fn test_rate_limit_applies_to_cache_population() {
+    let cache_dir = "tests/tmp_cache_rate_limit";
+    let _ = std::fs::remove_dir_all(cache_dir);
+    let oneio = oneio::OneIo::builder()
+        .backend("mem", MemBackend)
+        .rate_limit(TEST_TEXT.len() as u64)
+        .build()
+        .unwrap();
+
+    // The first download drains the one-second budget, so refetching the
+    // entry has to wait about a second.
+    let started = std::time::Instant::now();
+    for force in [false, true] {
+        let mut content = String::new();
+        oneio
+            .get_cache_reader("mem://test_data.txt", cache_dir, None, force)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, TEST_TEXT);
+    }
+    assert!(started.elapsed() >= Duration::from_millis(900));
+    std::fs::remove_dir_all(cache_dir).unwrap();
+
+    for builder in [
+        oneio::OneIo::builder().rate_limit(0),
+        oneio::OneIo::builder().rate_limit_per_host(0),
+    ] {
+        assert!(matches!(
+            builder.build(),
+            Err(oneio::OneIoError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidInput
+        ));
+    }
+}
+
+// ── Atomic writes ────────────────────────────────────────────────────────────
+
+/// Backend whose readers fail after yielding a few bytes.
+struct FailingBackend;
+
+impl oneio::Backend for FailingBackend {
+    fn reader_raw(&self, _path: &str) -> Result<Box<dyn Read + Send>, oneio::OneIoError> {
+        struct Failing(bool);
+        impl Read for Failing {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if std::mem::replace(&mut self.0, true) {
+                    return Err(std::io::Error::other("connection reset"));
+                }
+                buf[..7].copy_from_slice(b"partial");
+                Ok(7)
+            }
+        }
+        Ok(Box::new(Failing(false)))
+    }
+}
+
+fn atomic_oneio() -> oneio::OneIo {
+    oneio::OneIo::builder()
+        .atomic_writes(true)
+        .fsync_writes(true)
+        .backend("mem", MemBackend)
+        .backend("fail", FailingBackend)
+        .build()
+        .unwrap()
+}
+
+fn dir_file_names(dir: &str) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_atomic_writer_replaces_on_finish() {
+    let dir = "tests/tmp_atomic_writer";
+    let path = format!("{dir}/out.txt.gz");
+    let _ = std::fs::remove_dir_all(dir);
+
+    let oneio = atomic_oneio();
+    let mut writer = oneio.get_writer(&path).unwrap();
+    writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+    assert!(!std::path::Path::new(&path).exists());
+    writer.finish().unwrap();
+
+    assert_eq!(oneio.read_to_string_lossy(&path).unwrap(), TEST_TEXT);
+    assert_eq!(dir_file_names(dir), ["out.txt.gz"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_atomic_writer_drop_keeps_previous_file() {
+    let dir = "tests/tmp_atomic_drop";
+    let path = format!("{dir}/out.txt.gz");
+    let _ = std::fs::remove_dir_all(dir);
+
+    let oneio = atomic_oneio();
+    let mut writer = oneio.get_writer(&path).unwrap();
+    writer.write_all(b"previous").unwrap();
+    writer.finish().unwrap();
+
+    // An early return drops the writer without finishing it.
+    let write_all = || -> Result<(), oneio::OneIoError> {
+        let mut writer = oneio.get_writer(&path)?;
+        writer.write_all(b"half-written")?;
+        Err(oneio::OneIoError::NotSupported("job failed".to_string()))
+    };
+    assert!(write_all().is_err());
+
+    assert_eq!(oneio.read_to_string_lossy(&path).unwrap(), "previous");
+    assert_eq!(dir_file_names(dir), ["out.txt.gz"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_atomic_writer_panic_keeps_previous_file() {
+    let dir = "tests/tmp_atomic_panic";
+    let path = format!("{dir}/out.txt");
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(&path, "previous").unwrap();
+
+    let oneio = atomic_oneio();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut writer = oneio.get_writer(&path).unwrap();
+        writer.write_all(b"half-written").unwrap();
+        panic!("job failed halfway");
+    }));
+    assert!(result.is_err());
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous");
+    assert_eq!(dir_file_names(dir), ["out.txt"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_atomic_download() {
+    let dir = "tests/tmp_atomic_download";
+    let path = format!("{dir}/downloaded.txt");
+    let _ = std::fs::remove_dir_all(dir);
+
+    let oneio = atomic_oneio();
+    oneio.download("mem://test_data.txt", &path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), TEST_TEXT);
+
+    // A failed download leaves the previous file and no temp file behind.
+    assert!(oneio.download("fail://anything", &path).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), TEST_TEXT);
+    assert_eq!(dir_file_names(dir), ["downloaded.txt"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// ── OneIoWriter::finish ──────────────────────────────────────────────────────
+
+#[test]
+fn test_writer_finish_roundtrip_all_codecs() {
+    let dir = "tests/tmp_writer_finish";
+    let _ = std::fs::remove_dir_all(dir);
+
+    let extensions = [
+        "txt",
+        #[cfg(feature = "any_gz")]
+        "gz",
+        #[cfg(feature = "bz")]
+        "bz2",
+        #[cfg(feature = "lz")]
+        "lz4",
+        #[cfg(feature = "xz")]
+        "xz",
+        #[cfg(feature = "zstd")]
+        "zst",
+    ];
+    for ext in extensions {
+        let path = format!("{dir}/out.{ext}");
+        let mut writer = oneio::get_writer(&path).unwrap();
+        writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(
+            oneio::read_to_string_lossy(&path).unwrap(),
+            TEST_TEXT,
+            "{ext}"
+        );
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_writer_is_send() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let path = "tests/tmp_writer_send.txt";
+    let writer = oneio::get_writer(path).unwrap();
+    assert_send(&writer);
+
+    let handle = std::thread::spawn(move || {
+        let mut writer = writer;
+        writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+        writer.finish()
+    });
+    handle.join().unwrap().unwrap();
+    assert_eq!(std::fs::read_to_string(path).unwrap(), TEST_TEXT);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_writer_finish_reports_final_flush_error() {
+    // Writes to /dev/full fail with ENOSPC, but only once the buffer is flushed.
+    let mut writer = oneio::get_writer("/dev/full").unwrap();
+    writer.write_all(b"small payload").unwrap();
+    assert!(writer.finish().is_err());
+}
+
+// ── WriterOptions / get_writer_with_type ─────────────────────────────────────
+
+/// Writes `data` with `options` and returns the compressed size after
+/// checking that it round-trips.
+fn write_with_options(path: &str, data: &[u8], options: &oneio::WriterOptions) -> u64 {
+    let oneio = oneio::OneIo::new().unwrap();
+    let mut writer = oneio.get_writer_with_options(path, options).unwrap();
+    writer.write_all(data).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(oneio.read_to_bytes(path).unwrap(), data, "{path}");
+    std::fs::metadata(path).unwrap().len()
+}
+
+fn compressible_data() -> Vec<u8> {
+    (0..20_000u32)
+        .flat_map(|i| format!("line {i} value {}\n", i % 97).into_bytes())
+        .collect()
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_writer_options_gzip_level() {
+    let dir = "tests/tmp_writer_options_gz";
+    std::fs::create_dir_all(dir).unwrap();
+    let data = compressible_data();
+    let fast = write_with_options(
+        &format!("{dir}/fast.gz"),
+        &data,
+        &oneio::WriterOptions::new().level(1),
+    );
+    let best = write_with_options(
+        &format!("{dir}/best.gz"),
+        &data,
+        &oneio::WriterOptions::new().level(9),
+    );
+    assert!(best < fast, "level 9 ({best}) should beat level 1 ({fast})");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_writer_options_codec_parameters_roundtrip() {
+    let dir = "tests/tmp_writer_options_codecs";
+    std::fs::create_dir_all(dir).unwrap();
+    let data = compressible_data();
+
+    #[allow(unused_mut)]
+    let mut cases: Vec<(&str, oneio::WriterOptions)> = vec![("txt", oneio::WriterOptions::new())];
+    #[cfg(feature = "bz")]
+    cases.push(("bz2", oneio::WriterOptions::new().level(1)));
+    #[cfg(feature = "lz")]
+    cases.push((
+        "lz4",
+        oneio::WriterOptions::new()
+            .level(9)
+            .lz4_block_size(oneio::Lz4BlockSize::Max1MB)
+            .lz4_checksum(true),
+    ));
+    #[cfg(feature = "xz")]
+    cases.push((
+        "xz",
+        oneio::WriterOptions::new().xz_preset(1).xz_extreme(true),
+    ));
+    #[cfg(feature = "zstd")]
+    cases.push((
+        "zst",
+        oneio::WriterOptions::new()
+            .level(-3)
+            .zstd_long_distance_matching(true)
+            .zstd_window_log(27),
+    ));
+
+    for (ext, options) in cases {
+        write_with_options(&format!("{dir}/out.{ext}"), &data, &options);
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_zstd_large_window_requires_opt_in() {
+    let path = "tests/tmp_writer_options_large_window.zst";
+    let data = compressible_data();
+    let options = oneio::WriterOptions::new().zstd_window_log(30);
+    let oneio = oneio::OneIo::new().unwrap();
+    let mut writer = oneio.get_writer_with_options(path, &options).unwrap();
+    writer.write_all(&data).unwrap();
+    writer.finish().unwrap();
+
+    assert!(oneio.read_to_bytes(path).is_err());
+    let large_window = oneio::OneIo::builder()
+        .zstd_window_log_max(30)
+        .build()
+        .unwrap();
+    assert_eq!(large_window.read_to_bytes(path).unwrap(), data);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_writer_options_rejects_out_of_range_level() {
+    let oneio = oneio::OneIo::new().unwrap();
+    let path = "tests/tmp_writer_options_invalid.gz";
+    let result = oneio.get_writer_with_options(path, &oneio::WriterOptions::new().level(12));
+    match result {
+        Err(oneio::OneIoError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("level 12 should be rejected for gzip"),
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_get_writer_with_type_ignores_extension() {
+    let oneio = oneio::OneIo::new().unwrap();
+    let path = "tests/tmp_writer_with_type.bin";
+    let mut writer = oneio.get_writer_with_type(path, "gz").unwrap();
+    writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+    writer.finish().unwrap();
+
+    assert_eq!(&std::fs::read(path).unwrap()[..2], &[0x1f, 0x8b]);
+    let mut content = String::new();
+    oneio
+        .get_reader_with_type(path, "gz")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, TEST_TEXT);
+
+    // An empty type writes the bytes as is, even with a codec extension.
+    let raw_path = "tests/tmp_writer_with_type_raw.gz";
+    let mut writer = oneio.get_writer_with_type(raw_path, "").unwrap();
+    writer.write_all(TEST_TEXT.as_bytes()).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(std::fs::read_to_string(raw_path).unwrap(), TEST_TEXT);
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(raw_path).unwrap();
+}
+
+// ── Parallel compression ─────────────────────────────────────────────────────
+
+fn threaded_oneio() -> oneio::OneIo {
+    oneio::OneIo::builder()
+        .compression_threads(4)
+        .build()
+        .unwrap()
+}
+
+/// A few MiB of moderately compressible input, spanning several gzip blocks.
+fn multi_block_data() -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..3_500_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            b"abcdefgh\n"[(state % 9) as usize]
+        })
+        .collect()
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_parallel_gzip_writes_multi_member_stream() {
+    let path = "tests/tmp_parallel.gz";
+    let data = multi_block_data();
+    let mut writer = threaded_oneio().get_writer(path).unwrap();
+    writer.write_all(&data).unwrap();
+    writer.finish().unwrap();
+
+    let compressed = std::fs::read(path).unwrap();
+    let mut decoded = Vec::new();
+    flate2::read::MultiGzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, data);
+
+    // Each 1 MiB block is its own member, so the first member alone is short.
+    let mut first_member = Vec::new();
+    flate2::read::GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut first_member)
+        .unwrap();
+    assert_eq!(first_member.len(), 1024 * 1024);
+
+    // oneio's own reader decodes every member.
+    assert_eq!(oneio::read_to_bytes(path).unwrap(), data);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_parallel_gzip_empty_output_is_valid() {
+    let path = "tests/tmp_parallel_empty.gz";
+    threaded_oneio().get_writer(path).unwrap().finish().unwrap();
+    let mut decoded = Vec::new();
+    flate2::read::MultiGzDecoder::new(std::fs::File::open(path).unwrap())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert!(decoded.is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_parallel_zstd_and_xz_roundtrip() {
+    let dir = "tests/tmp_parallel_codecs";
+    std::fs::create_dir_all(dir).unwrap();
+    let data = multi_block_data();
+    let oneio = threaded_oneio();
+
+    let extensions = [
+        "txt",
+        #[cfg(feature = "xz")]
+        "xz",
+        #[cfg(feature = "zstd")]
+        "zst",
+    ];
+    for ext in extensions {
+        let path = format!("{dir}/out.{ext}");
+        let mut writer = oneio.get_writer(&path).unwrap();
+        writer.write_all(&data).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(oneio.read_to_bytes(&path).unwrap(), data, "{ext}");
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// ── Concatenated gzip members / bzip2 streams ────────────────────────────────
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_local_gzip_multi_member() {
+    test_read("tests/test_data_multi.txt.gz");
+}
+
+#[cfg(feature = "bz")]
+#[test]
+fn test_local_bzip2_multi_stream() {
+    test_read("tests/test_data_multi.txt.bz2");
+}
+
+#[test]
+fn test_multi_member_decoding_opt_out_reads_first_member() {
+    let oneio = oneio::OneIo::builder()
+        .multi_member_decoding(false)
+        .build()
+        .unwrap();
+    let fixtures = [
+        #[cfg(feature = "any_gz")]
+        "tests/test_data_multi.txt.gz",
+        #[cfg(feature = "bz")]
+        "tests/test_data_multi.txt.bz2",
+    ];
+    for fixture in fixtures {
+        assert_eq!(
+            oneio.read_to_string_lossy(fixture).unwrap(),
+            "OneIO test file.\n",
+            "{fixture}"
+        );
+    }
+}
+
+#[cfg(feature = "any_gz")]
+#[test]
+fn test_multi_member_with_type_override_and_sniffing() {
+    let dir = "tests/tmp_multi_member";
+    let path = copy_fixture(dir, "tests/test_data_multi.txt.gz", "download");
+
+    let mut content = String::new();
+    oneio::OneIo::new()
+        .unwrap()
+        .get_reader_with_type(&path, "gz")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, TEST_TEXT);
+
+    let sniffing = sniffing_oneio(oneio::CompressionDetection::SniffOnly);
+    assert_eq!(sniffing.read_to_string_lossy(&path).unwrap(), TEST_TEXT);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// ── Tar archive members ──────────────────────────────────────────────────────
+
+/// Builds `{dir}/bundle.tar.gz` holding a plain CSV, a gzipped CSV inside a
+/// directory and a member with a path longer than the 100-byte ustar limit.
+#[cfg(all(feature = "archive", feature = "any_gz"))]
+fn build_tar_fixture(dir: &str) -> (String, String) {
+    std::fs::create_dir_all(dir).unwrap();
+    let inner_gz = format!("{dir}/data.csv.gz");
+    let mut writer = oneio::get_writer(&inner_gz).unwrap();
+    writer.write_all(b"a,b\n3,4\n").unwrap();
+    writer.finish().unwrap();
+
+    let long_name = format!("{}/long.txt", "nested".repeat(20));
+    let archive_path = format!("{dir}/bundle.tar.gz");
+    let mut builder = tar::Builder::new(oneio::get_writer(&archive_path).unwrap());
+    let mut append = |name: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, data).unwrap();
+    };
+    append("file.csv", b"a,b\n1,2\n");
+    append("inner/data.csv.gz", &std::fs::read(&inner_gz).unwrap());
+    append(&long_name, TEST_TEXT.as_bytes());
+    builder.into_inner().unwrap().finish().unwrap();
+    (archive_path, long_name)
+}
+
+#[cfg(all(feature = "archive", feature = "any_gz"))]
+#[test]
+fn test_read_tar_members_via_path_syntax() {
+    let dir = "tests/tmp_tar_members";
+    let (archive, long_name) = build_tar_fixture(dir);
+
+    assert_eq!(
+        oneio::read_to_string_lossy(&format!("{archive}!/file.csv")).unwrap(),
+        "a,b\n1,2\n"
+    );
+    // The member is decompressed by its own suffix.
+    assert_eq!(
+        oneio::read_to_string_lossy(&format!("{archive}!/inner/data.csv.gz")).unwrap(),
+        "a,b\n3,4\n"
+    );
+    assert_eq!(
+        oneio::read_to_string_lossy(&format!("{archive}!/{long_name}")).unwrap(),
+        TEST_TEXT
+    );
+    let missing = oneio::get_reader(&format!("{archive}!/nope.csv"));
+    assert!(
+        matches!(missing, Err(oneio::OneIoError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound)
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(feature = "archive", feature = "any_gz"))]
+#[test]
+fn test_list_archive_streams_names_and_sizes() {
+    let dir = "tests/tmp_tar_list";
+    let (archive, long_name) = build_tar_fixture(dir);
+
+    let entries: Vec<oneio::ArchiveEntry> = oneio::list_archive(&archive)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let names: Vec<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+    assert_eq!(names, ["file.csv", "inner/data.csv.gz", long_name.as_str()]);
+    assert_eq!(entries[0].size, 8);
+    assert_eq!(entries[2].size, TEST_TEXT.len() as u64);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.kind == oneio::ArchiveEntryKind::File));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(feature = "archive", feature = "any_gz"))]
+#[test]
+fn test_read_tar_member_over_http() {
+    let dir = "tests/tmp_tar_http";
+    let (archive, _) = build_tar_fixture(dir);
+    let (url, handle) = spawn_range_http_server(std::fs::read(&archive).unwrap(), 1);
+
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+    let content = oneio
+        .read_to_string_lossy(&format!("{url}.tar.gz!/file.csv"))
+        .unwrap();
+    assert_eq!(content, "a,b\n1,2\n");
+    handle.join().unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "archive")]
+#[test]
+fn test_tar_member_roundtrip_per_suffix() {
+    let dir = "tests/tmp_tar_suffixes";
+    let _ = std::fs::remove_dir_all(dir);
+    let suffixes: &[&str] = &[
+        #[cfg(feature = "any_gz")]
+        "tgz",
+        #[cfg(feature = "bz")]
+        "tbz",
+        #[cfg(feature = "bz")]
+        "tbz2",
+        #[cfg(feature = "xz")]
+        "txz",
+        #[cfg(feature = "zstd")]
+        "tzst",
+        #[cfg(feature = "lz")]
+        "tlz4",
+    ];
+    for suffix in suffixes {
+        let path = format!("{dir}/bundle.{suffix}");
+        let mut builder = tar::Builder::new(oneio::get_writer(&path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(TEST_TEXT.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "file.txt", TEST_TEXT.as_bytes())
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        // The archive on disk is compressed, not a plain tar.
+        let raw = std::fs::read(&path).unwrap();
+        assert_ne!(raw.get(257..262), Some(&b"ustar"[..]), "{suffix}");
+        assert_eq!(
+            oneio::read_to_string_lossy(&format!("{path}!/file.txt")).unwrap(),
+            TEST_TEXT,
+            "{suffix}"
+        );
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+// ── Zip archive members ──────────────────────────────────────────────────────
+
+#[cfg(all(feature = "zip", feature = "any_gz"))]
+#[test]
+fn test_read_single_member_zip() {
+    assert_eq!(
+        oneio::read_to_string_lossy("tests/test_data.txt.zip").unwrap(),
+        TEST_TEXT
+    );
+    // An archive with several files is passed through as raw bytes.
+    let mut raw = Vec::new();
+    oneio::get_reader("tests/test_archive.zip")
+        .unwrap()
+        .read_to_end(&mut raw)
+        .unwrap();
+    assert_eq!(raw, std::fs::read("tests/test_archive.zip").unwrap());
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn test_read_zip_members_via_path_syntax() {
+    let read = |member: &str| {
+        oneio::read_to_string_lossy(&format!("tests/test_archive.zip!/{member}")).unwrap()
+    };
+    assert_eq!(read("docs/stored.txt"), TEST_TEXT);
+    #[cfg(feature = "any_gz")]
+    {
+        assert_eq!(read("docs/deflated.txt"), TEST_TEXT);
+        // The member is decompressed by its own suffix.
+        assert_eq!(read("data.txt.gz"), TEST_TEXT);
+    }
+    #[cfg(feature = "bz")]
+    assert_eq!(read("docs/bzip2.txt"), TEST_TEXT);
+
+    let lzma = oneio::get_reader("tests/test_archive.zip!/docs/lzma.txt");
+    assert!(matches!(lzma, Err(oneio::OneIoError::NotSupported(_))));
+    let missing = oneio::get_reader("tests/test_archive.zip!/nope.txt");
+    assert!(
+        matches!(missing, Err(oneio::OneIoError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound)
+    );
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn test_zip_member_crc_mismatch_is_an_error() {
+    let dir = "tests/tmp_zip_corrupt";
+    std::fs::create_dir_all(dir).unwrap();
+    let mut bytes = std::fs::read("tests/test_archive.zip").unwrap();
+    // The first copy of the text is the data of the stored member.
+    let offset = bytes
+        .windows(TEST_TEXT.len())
+        .position(|window| window == TEST_TEXT.as_bytes())
+        .unwrap();
+    bytes[offset] ^= 0x20;
+    let archive = format!("{dir}/corrupt.zip");
+    std::fs::write(&archive, bytes).unwrap();
+
+    let mut reader = oneio::get_reader(&format!("{archive}!/docs/stored.txt")).unwrap();
+    let mut content = Vec::new();
+    let err = reader.read_to_end(&mut content).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn test_list_zip_archive() {
+    let entries: Vec<oneio::ArchiveEntry> = oneio::list_archive("tests/test_archive.zip")
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let names: Vec<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "docs/",
+            "docs/stored.txt",
+            "docs/deflated.txt",
+            "docs/bzip2.txt",
+            "docs/lzma.txt",
+            "data.txt.gz"
+        ]
+    );
+    assert_eq!(entries[0].kind, oneio::ArchiveEntryKind::Directory);
+    assert_eq!(entries[1].kind, oneio::ArchiveEntryKind::File);
+    assert_eq!(entries[1].size, TEST_TEXT.len() as u64);
+}
+
+#[cfg(all(feature = "zip", feature = "http"))]
+#[test]
+fn test_read_zip_member_over_http() {
+    let body = std::fs::read("tests/test_archive.zip").unwrap();
+    // The archive fits in the first read-ahead block: one ranged request.
+    let (url, handle) = spawn_range_http_server(body, 1);
+
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+    let content = oneio
+        .read_to_string_lossy(&format!("{url}.zip!/docs/stored.txt"))
+        .unwrap();
+    assert_eq!(content, TEST_TEXT);
+    let requests = handle.join().unwrap();
+    assert_ne!(requests, ["GET"]);
+}
+
+#[cfg(all(feature = "zip", feature = "http"))]
+#[test]
+fn test_read_multi_file_zip_over_http_fetches_once() {
+    let body = std::fs::read("tests/test_archive.zip").unwrap();
+    // Passing the archive through reuses the bytes fetched to index it.
+    let (url, handle) = spawn_range_http_server(body.clone(), 1);
+
+    let oneio = oneio::OneIo::builder().no_proxy().build().unwrap();
+    let mut raw = Vec::new();
+    oneio
+        .get_reader(&format!("{url}.zip"))
+        .unwrap()
+        .read_to_end(&mut raw)
+        .unwrap();
+    assert_eq!(raw, body);
+    assert_eq!(handle.join().unwrap().len(), 1);
+}
@@ -273,6 +273,49 @@ fn test_r2_multipart_80mb() {
     cleanup_test_objects(&bucket, &prefix);
 }
 
+#[test]
+#[ignore = "requires R2 credentials"]
+fn test_r2_streaming_writer_small() {
+    use std::io::Write;
+
+    let (bucket, _guard) = begin_s3_test();
+    let prefix = test_prefix("writer-small");
+    let path = format!("s3://{bucket}/{prefix}writer-small.txt.gz");
+    let data = generate_test_data(64 * 1024, "writer");
+
+    let mut writer = oneio::get_writer(&path).unwrap();
+    writer.write_all(&data).unwrap();
+    drop(writer);
+
+    assert_eq!(oneio::read_to_bytes(&path).unwrap(), data);
+
+    cleanup_test_objects(&bucket, &prefix);
+}
+
+#[test]
+#[ignore = "requires R2 credentials"]
+fn test_r2_streaming_writer_multipart() {
+    use std::io::Write;
+
+    let (bucket, _guard) = begin_s3_test();
+    let prefix = test_prefix("writer-multipart");
+    let key = format!("{prefix}writer-multipart.bin");
+    let size = 20 * 1024 * 1024;
+    let data = generate_test_data(size, "streamingwriter");
+
+    let mut writer = oneio::get_writer(&format!("s3://{bucket}/{key}")).unwrap();
+    for chunk in data.chunks(100_000) {
+        writer.write_all(chunk).unwrap();
+    }
+    drop(writer);
+
+    let stats = oneio::s3_stats(&bucket, &key).unwrap();
+    assert_eq!(stats.content_length, size as u64);
+    assert_stream_matches(&bucket, &key, &data);
+
+    cleanup_test_objects(&bucket, &prefix);
+}
+
 // ========== Download Tests ==========
 
 #[test]
@@ -339,6 +382,77 @@ fn test_r2_list_objects() {
     cleanup_test_objects(&bucket, &prefix);
 }
 
+#[test]
+#[ignore = "requires R2 credentials"]
+fn test_r2_list_entries_with_sizes() {
+    let (bucket, _guard) = begin_s3_test();
+    let prefix = test_prefix("list-entries");
+
+    for key in ["a.txt", "sub/b.txt"] {
+        let data = generate_test_data(1024, key);
+        let temp_path = TempFile::new(&data);
+        oneio::s3_upload(
+            &bucket,
+            &format!("{prefix}{key}"),
+            temp_path.as_ref().to_str().unwrap(),
+        )
+        .unwrap();
+    }
+
+    // No trailing slash: the prefix is listed as a directory.
+    let url = format!("s3://{bucket}/{}", prefix.trim_end_matches('/'));
+    let entries = oneio::list(&url).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "a.txt");
+    assert_eq!(entries[0].size, Some(1024));
+    assert!(entries[0].last_modified.is_some());
+    assert_eq!(entries[0].path, format!("s3://{bucket}/{prefix}a.txt"));
+    assert_eq!(entries[1].name, "sub");
+    assert!(entries[1].is_dir);
+    assert_eq!(entries[1].size, None);
+
+    cleanup_test_objects(&bucket, &prefix);
+}
+
+#[test]
+#[ignore = "requires R2 credentials"]
+fn test_r2_glob() {
+    let (bucket, _guard) = begin_s3_test();
+    let prefix = test_prefix("glob");
+
+    for key in [
+        "01/updates.a.gz",
+        "01/rib.a.gz",
+        "02/updates.b.gz",
+        "02/x/updates.c.gz",
+    ] {
+        let data = generate_test_data(16, key);
+        let temp_path = TempFile::new(&data);
+        oneio::s3_upload(
+            &bucket,
+            &format!("{prefix}{key}"),
+            temp_path.as_ref().to_str().unwrap(),
+        )
+        .unwrap();
+    }
+
+    let matches = oneio::glob(&format!("s3://{bucket}/{prefix}*/updates.*.gz")).unwrap();
+    assert_eq!(
+        matches,
+        [
+            format!("s3://{bucket}/{prefix}01/updates.a.gz"),
+            format!("s3://{bucket}/{prefix}02/updates.b.gz"),
+        ]
+    );
+    let matches = oneio::glob(&format!("s3://{bucket}/{prefix}**/{{rib,updates}}.c.gz")).unwrap();
+    assert_eq!(
+        matches,
+        [format!("s3://{bucket}/{prefix}02/x/updates.c.gz")]
+    );
+
+    cleanup_test_objects(&bucket, &prefix);
+}
+
 // ========== Metadata Tests ==========
 
 #[test]